@@ -0,0 +1,182 @@
+use std::{
+    collections::HashSet,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use odesli_rs::{EntityType, LinksAPIResult, OdesliClient, OdesliError, Platform};
+use tokio::{
+    sync::{mpsc, Mutex, Semaphore},
+    time::Instant,
+};
+
+/// A single input of a batch, parsed from one line.
+#[derive(Clone, Debug)]
+pub enum BatchInput {
+    /// A platform URL.
+    Url(String),
+    /// A `platform:type:id` triple.
+    Id { platform: Platform, entity_type: EntityType, id: String },
+}
+
+impl BatchInput {
+    /// Parse a line of a batch file.
+    ///
+    /// Returns `None` for blank lines and `#` comments.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let mut parts = line.splitn(3, ':');
+        if let (Some(platform), Some(entity_type), Some(id)) =
+            (parts.next(), parts.next(), parts.next())
+        {
+            if let (Ok(platform), Ok(entity_type)) =
+                (Platform::from_str(platform), EntityType::from_str(entity_type))
+            {
+                return Some(Self::Id { platform, entity_type, id: id.to_string() });
+            }
+        }
+
+        Some(Self::Url(line.to_string()))
+    }
+
+    /// The request [`BatchInput::resolve`] sends.
+    pub fn request_url(&self, client: &OdesliClient) -> Result<odesli_rs::Url, OdesliError> {
+        match self {
+            Self::Url(url) => client.request_url_by_url(url),
+            Self::Id { platform, entity_type, id } => {
+                client.request_url_by_id(id, platform, entity_type)
+            }
+        }
+    }
+
+    pub async fn resolve(&self, client: &OdesliClient) -> Result<LinksAPIResult, OdesliError> {
+        match self {
+            Self::Url(url) => client.get_by_url(url).await,
+            Self::Id { platform, entity_type, id } => {
+                client.get_by_id(id, platform, entity_type).await
+            }
+        }
+    }
+}
+
+/// Spaces out requests so that no more than a given number are sent per
+/// minute, across all the tasks sharing it.
+pub struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn per_minute(requests: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(60) / requests.max(1),
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait until the next request may be sent.
+    pub async fn acquire(&self) {
+        let slot = {
+            let mut next_slot = self.next_slot.lock().await;
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + self.interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+/// An append-only record of the inputs a batch has processed, so that an
+/// interrupted batch can be resumed.
+///
+/// Each line is a JSON object with the `input` and whether it succeeded.
+pub struct Journal {
+    file: File,
+}
+
+impl Journal {
+    /// Open the journal at `path`, starting a new one unless `resume` is set.
+    pub fn open(path: &Path, resume: bool) -> std::io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(resume)
+            .truncate(!resume)
+            .open(path)?;
+        Ok(Self { file })
+    }
+
+    /// The inputs that were successfully processed according to the journal
+    /// at `path`, if it exists.
+    pub fn succeeded_inputs(path: &Path) -> std::io::Result<HashSet<String>> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(HashSet::new()),
+            Err(err) => return Err(err),
+        };
+
+        let mut succeeded = HashSet::new();
+        for line in BufReader::new(file).lines() {
+            // A line cut short by a crash is ignored, so its input is retried.
+            let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line?) else {
+                continue;
+            };
+            if let (Some(input), Some(succeeded_entry)) =
+                (entry["input"].as_str(), entry["succeeded"].as_bool())
+            {
+                if succeeded_entry {
+                    succeeded.insert(input.to_string());
+                } else {
+                    succeeded.remove(input);
+                }
+            }
+        }
+        Ok(succeeded)
+    }
+
+    /// Record that `input` was processed.
+    pub fn record(&mut self, input: &str, succeeded: bool) -> std::io::Result<()> {
+        let entry = serde_json::json!({ "input": input, "succeeded": succeeded });
+        writeln!(self.file, "{}", entry)?;
+        self.file.sync_data()
+    }
+}
+
+/// Resolve all the `inputs` with at most `concurrency` requests in flight,
+/// sending each outcome (along with the index of its input) as soon as it is
+/// available.
+pub fn resolve_all(
+    client: OdesliClient,
+    inputs: Vec<(usize, BatchInput)>,
+    concurrency: usize,
+    rate_limiter: Option<RateLimiter>,
+) -> mpsc::UnboundedReceiver<(usize, Result<LinksAPIResult, OdesliError>)> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+    let rate_limiter = rate_limiter.map(Arc::new);
+
+    for (index, input) in inputs {
+        let client = client.clone();
+        let sender = sender.clone();
+        let semaphore = semaphore.clone();
+        let rate_limiter = rate_limiter.clone();
+
+        tokio::spawn(async move {
+            let _permit = semaphore.acquire_owned().await.expect("semaphore is never closed");
+            if let Some(rate_limiter) = rate_limiter {
+                rate_limiter.acquire().await;
+            }
+            let _ = sender.send((index, input.resolve(&client).await));
+        });
+    }
+
+    receiver
+}
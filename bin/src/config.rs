@@ -0,0 +1,221 @@
+//! The configuration file, `$XDG_CONFIG_HOME/odesli/config.toml` (or
+//! `~/.config/odesli/config.toml`) by default.
+//!
+//! Every setting is optional. Named profiles live in `[profile.<name>]`
+//! sections, with the same settings as the top level, and override it when
+//! selected with `--profile`:
+//!
+//! ```toml
+//! api_key = "..."            # or, to keep it out of this file:
+//! api_key_file = "/path/to/key" # only readable by the user (chmod 600)
+//! api_key_command = "pass show odesli"
+//! country = "US"
+//! format = "text"
+//! preferred_platforms = ["spotify", "appleMusic"]
+//! base_url = "https://api.song.link"
+//!
+//! [cache]
+//! enabled = true
+//! path = "/path/to/catalog.sqlite3"
+//! prefer = true
+//! max_age = "7d"
+//!
+//! [profile.work]
+//! api_key = "..."
+//! country = "GB"
+//! ```
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The settings of the configuration file, or of one of its profiles.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_file: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_platforms: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub cache: CacheSettings,
+    #[serde(default)]
+    pub history: HistorySettings,
+    #[serde(default)]
+    pub watch: WatchSettings,
+}
+
+/// The `[cache]` section, configuring the catalog of looked up IDs.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct CacheSettings {
+    /// Record lookups in the catalog.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Where the catalog is stored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// Answer lookups by ID from the catalog when possible.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefer: Option<bool>,
+    /// Ignore catalog entries older than this, e.g. `"7d"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<String>,
+}
+
+/// The `[history]` section, configuring the record of the lookups made.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct HistorySettings {
+    /// Record lookups in the history.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Where the history is stored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// Keep at most this many lookups.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_entries: Option<usize>,
+    /// Drop the lookups older than this, e.g. `"90d"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<String>,
+    /// Also record the lookups of `batch`, `cache warm`, `watch` and
+    /// `compare`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bulk: Option<bool>,
+}
+
+/// The `[watch]` section, configuring `odesli watch`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct WatchSettings {
+    /// Where the last results of the watched links are stored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// How long to wait between checks, e.g. `"1h"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<String>,
+    /// The URL to POST a JSON payload to when platforms appear or disappear.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
+}
+
+impl Settings {
+    /// These settings, with those set in `other` replacing them.
+    fn overlay(self, other: Settings) -> Settings {
+        Settings {
+            api_key: other.api_key.or(self.api_key),
+            api_key_file: other.api_key_file.or(self.api_key_file),
+            api_key_command: other.api_key_command.or(self.api_key_command),
+            country: other.country.or(self.country),
+            format: other.format.or(self.format),
+            preferred_platforms: other.preferred_platforms.or(self.preferred_platforms),
+            base_url: other.base_url.or(self.base_url),
+            cache: CacheSettings {
+                enabled: other.cache.enabled.or(self.cache.enabled),
+                path: other.cache.path.or(self.cache.path),
+                prefer: other.cache.prefer.or(self.cache.prefer),
+                max_age: other.cache.max_age.or(self.cache.max_age),
+            },
+            history: HistorySettings {
+                enabled: other.history.enabled.or(self.history.enabled),
+                path: other.history.path.or(self.history.path),
+                max_entries: other.history.max_entries.or(self.history.max_entries),
+                max_age: other.history.max_age.or(self.history.max_age),
+                bulk: other.history.bulk.or(self.history.bulk),
+            },
+            watch: WatchSettings {
+                path: other.watch.path.or(self.watch.path),
+                interval: other.watch.interval.or(self.watch.interval),
+                webhook: other.watch.webhook.or(self.watch.webhook),
+            },
+        }
+    }
+}
+
+/// A parsed configuration file.
+#[derive(Debug, Default)]
+pub struct Config {
+    /// The file the configuration was read from, `None` if there is none.
+    pub path: Option<PathBuf>,
+    /// The top level settings.
+    pub settings: Settings,
+    /// The `[profile.<name>]` sections.
+    pub profiles: HashMap<String, Settings>,
+}
+
+impl Config {
+    /// Read the configuration from `path`, or from the default location if
+    /// `None`. Only a missing default file is not an error.
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        let (path, explicit) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match default_config_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if !explicit && err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::default())
+            }
+            Err(err) => return Err(format!("Failed to read '{}': {}", path.display(), err)),
+        };
+        Self::parse(&contents, path)
+    }
+
+    fn parse(contents: &str, path: PathBuf) -> Result<Self, String> {
+        let invalid =
+            |err: toml::de::Error| format!("Invalid config '{}': {}", path.display(), err);
+
+        let mut table = contents.parse::<toml::Table>().map_err(invalid)?;
+        let profiles = match table.remove("profile") {
+            Some(profiles) => profiles.try_into().map_err(invalid)?,
+            None => HashMap::new(),
+        };
+        let settings = toml::Value::Table(table).try_into().map_err(invalid)?;
+
+        Ok(Self { path: Some(path), settings, profiles })
+    }
+
+    /// The settings in effect with `profile` selected.
+    pub fn settings(&self, profile: Option<&str>) -> Result<Settings, String> {
+        let Some(profile) = profile else {
+            return Ok(self.settings.clone());
+        };
+        match self.profiles.get(profile) {
+            Some(overrides) => Ok(self.settings.clone().overlay(overrides.clone())),
+            None => Err(match self.path.as_ref() {
+                Some(path) => format!("Unknown profile '{}' in '{}'", profile, path.display()),
+                None => format!("Unknown profile '{}', there is no config file", profile),
+            }),
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/odesli/config.toml`, falling back to `~/.config` when
+/// `XDG_CONFIG_HOME` is not set, and to the platform's config dir without a
+/// home dir.
+pub fn default_config_path() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+        .or_else(dirs::config_dir)?;
+    Some(config_dir.join("odesli").join("config.toml"))
+}
@@ -0,0 +1,75 @@
+use odesli_rs::OdesliError;
+
+/// The kinds of failures and the exit codes they end the CLI with, so that
+/// scripts can tell them apart. The kinds are those of
+/// [`crate::utils::error_kind`], plus `noMatch`, and the verdicts of
+/// `compare` other than the same release.
+pub const EXIT_CODES: [(&str, i32, &str); 10] = [
+    ("other", 1, "any other failure"),
+    ("badInput", 2, "invalid arguments or input"),
+    ("noMatch", 3, "a requested platform has no match"),
+    ("network", 4, "the API could not be reached"),
+    ("rateLimited", 5, "too many requests, retry later or use an API key"),
+    ("notFound", 6, "the API found nothing for the input"),
+    ("parseError", 7, "the response of the API could not be parsed"),
+    ("api", 8, "the API failed with another status code"),
+    ("different", 9, "the compared links are for different releases"),
+    ("uncertain", 10, "the compared links may or may not be for the same release"),
+];
+
+/// The exit code for a kind of failure.
+pub fn exit_code(kind: &str) -> i32 {
+    EXIT_CODES.iter().find(|(name, _, _)| *name == kind).map_or(1, |(_, code, _)| *code)
+}
+
+/// The table of [`EXIT_CODES`], for the help.
+pub fn exit_codes_help() -> String {
+    let mut help = String::from("Exit codes:\n  0  success");
+    for (kind, code, description) in EXIT_CODES {
+        help.push_str(&format!("\n  {code}  {description} ({kind})"));
+    }
+    help
+}
+
+/// An error ending the process with the exit code of its `kind`.
+#[derive(Debug)]
+pub struct ExitError {
+    pub kind: &'static str,
+    pub message: String,
+}
+
+impl ExitError {
+    pub fn new(kind: &'static str, message: impl Into<String>) -> Self {
+        Self { kind, message: message.into() }
+    }
+}
+
+impl std::fmt::Display for ExitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ExitError {}
+
+/// Report `error` on stderr and exit with the code of its kind. With `json`,
+/// the report is a JSON object with the kind, message, and the status code and
+/// body of the response, if any.
+pub fn exit_with(error: &(dyn std::error::Error + 'static), json: bool) -> ! {
+    let report = match error.downcast_ref::<OdesliError>() {
+        Some(error) => crate::utils::error_to_json(error),
+        None => serde_json::json!({
+            "kind": error.downcast_ref::<ExitError>().map_or("other", |error| error.kind),
+            "message": error.to_string(),
+            "statusCode": null,
+            "body": null,
+        }),
+    };
+
+    if json {
+        eprintln!("{}", report);
+    } else {
+        eprintln!("Error: {}", error);
+    }
+    std::process::exit(exit_code(report["kind"].as_str().unwrap_or_default()))
+}
@@ -0,0 +1,408 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use odesli_rs::LinksAPIResult;
+use serde::{Deserialize, Serialize};
+
+use crate::{lock::FileLock, options::GlobalOptions};
+
+/// How many lookups the history keeps when the configuration sets no limit.
+pub const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+/// How many bytes to read at a time from the end of the history, looking for
+/// the last entry.
+const TAIL_CHUNK: u64 = 64 * 1024;
+
+/// A lookup recorded in the [`History`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    /// The number of the entry, increasing with each lookup.
+    pub id: u64,
+    /// When the lookup was made, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The subcommand that made the lookup, e.g. `get-url`.
+    pub command: String,
+    /// What was looked up, as given to `command`.
+    pub input: String,
+    /// The country the lookup was made for.
+    pub country: String,
+    /// The title and artist of the entity found.
+    pub summary: String,
+    pub result: LinksAPIResult,
+}
+
+impl HistoryEntry {
+    /// This entry without its result, as listed by `history list`.
+    pub fn overview(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "timestamp": self.timestamp,
+            "command": self.command,
+            "input": self.input,
+            "country": self.country,
+            "summary": self.summary,
+        })
+    }
+
+    /// How long ago the lookup was made.
+    pub fn age(&self) -> Duration {
+        now().saturating_sub(Duration::from_secs(self.timestamp))
+    }
+
+    /// Whether all the words of `query` are found in the input, the summary or
+    /// the links of this entry, ignoring case.
+    pub fn matches(&self, query: &str) -> bool {
+        let mut haystack = format!("{}\n{}\n{}", self.input, self.summary, self.result.page_url);
+        for link in self.result.links_by_platform.values() {
+            haystack.push('\n');
+            haystack.push_str(&link.url);
+        }
+        let haystack = haystack.to_lowercase();
+        query.to_lowercase().split_whitespace().all(|word| haystack.contains(word))
+    }
+}
+
+/// `<title> by <artist>` of the entity `result` was looked up for.
+pub fn summarize(result: &LinksAPIResult) -> String {
+    let entity = result.entities_by_unique_id.get(&result.entity_unique_id);
+    let title = entity.and_then(|entity| entity.title.as_deref()).unwrap_or("<NA>");
+    let artist = entity.and_then(|entity| entity.artist_name.as_deref()).unwrap_or("<NA>");
+    format!("{title} by {artist}")
+}
+
+/// The time since the Unix epoch.
+pub fn now() -> Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+/// The lookups made from the command line, stored as a JSON lines file of
+/// [`HistoryEntry`]s, oldest first.
+///
+/// Lookups are appended to the file. The entries beyond `max_entries` or older
+/// than `max_age` are not listed, and are dropped from the file once it exceeds
+/// these limits by a tenth, so that it is not rewritten at every lookup.
+pub struct History {
+    path: PathBuf,
+    max_entries: usize,
+    max_age: Option<Duration>,
+}
+
+impl History {
+    pub fn new(path: PathBuf, max_entries: usize, max_age: Option<Duration>) -> Self {
+        Self { path, max_entries, max_age }
+    }
+
+    /// The recorded entries within the retention limits, oldest first.
+    pub fn entries(&self) -> std::io::Result<Vec<HistoryEntry>> {
+        let mut entries = self.read_entries()?;
+        self.retain(&mut entries);
+        Ok(entries)
+    }
+
+    /// The entry numbered `id`, if it is still recorded.
+    pub fn get(&self, id: u64) -> std::io::Result<Option<HistoryEntry>> {
+        Ok(self.entries()?.into_iter().find(|entry| entry.id == id))
+    }
+
+    /// Record that `input` was looked up with `command`, dropping the entries
+    /// beyond the retention limits if there are too many.
+    pub fn record(
+        &self,
+        command: &str,
+        input: &str,
+        result: &LinksAPIResult,
+    ) -> std::io::Result<HistoryEntry> {
+        let _lock = FileLock::acquire(&self.path)?;
+        let entry = HistoryEntry {
+            id: self.last_entry()?.map_or(1, |last| last.id + 1),
+            timestamp: now().as_secs(),
+            command: command.to_string(),
+            input: input.to_string(),
+            country: result.user_country.clone(),
+            summary: summarize(result),
+            result: result.clone(),
+        };
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        drop(file);
+
+        if self.exceeds_limits(&entry)? {
+            let mut entries = self.read_entries()?;
+            self.retain(&mut entries);
+            self.rewrite(&entries)?;
+        }
+        Ok(entry)
+    }
+
+    /// All the entries of the file, oldest first. Lines that cannot be parsed,
+    /// e.g. cut short by a crash, are skipped.
+    fn read_entries(&self) -> std::io::Result<Vec<HistoryEntry>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            if let Ok(entry) = serde_json::from_str::<HistoryEntry>(&line?) {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Drop the `entries` beyond the retention limits.
+    fn retain(&self, entries: &mut Vec<HistoryEntry>) {
+        if let Some(max_age) = self.max_age {
+            entries.retain(|entry| entry.age() <= max_age);
+        }
+        entries.drain(..entries.len().saturating_sub(self.max_entries));
+    }
+
+    /// Whether the file, ending with `last`, holds a tenth more entries than
+    /// `max_entries`, or entries a tenth older than `max_age`. The entries are
+    /// counted from their IDs, which follow each other.
+    fn exceeds_limits(&self, last: &HistoryEntry) -> std::io::Result<bool> {
+        let file = BufReader::new(File::open(&self.path)?);
+        let mut first = None;
+        for line in file.lines() {
+            if let Ok(entry) = serde_json::from_str::<HistoryEntry>(&line?) {
+                first = Some(entry);
+                break;
+            }
+        }
+        let Some(first) = first else {
+            return Ok(false);
+        };
+
+        let count = last.id.saturating_sub(first.id).saturating_add(1);
+        let max_entries = self.max_entries.saturating_add(self.max_entries / 10);
+        let too_old =
+            self.max_age.is_some_and(|max_age| first.age() > max_age.saturating_add(max_age / 10));
+        Ok(count > max_entries as u64 || too_old)
+    }
+
+    /// The last entry of the file, read from its end so that recording a
+    /// lookup does not read the whole history.
+    fn last_entry(&self) -> std::io::Result<Option<HistoryEntry>> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        let mut end = file.metadata()?.len();
+        let mut tail = Vec::new();
+        while end > 0 {
+            let start = end.saturating_sub(TAIL_CHUNK);
+            let mut chunk = vec![0; (end - start) as usize];
+            file.seek(SeekFrom::Start(start))?;
+            file.read_exact(&mut chunk)?;
+            chunk.append(&mut tail);
+            tail = chunk;
+            end = start;
+
+            let mut lines = tail.split(|byte| *byte == b'\n').collect::<Vec<_>>();
+            // The first line is cut, unless it starts the file.
+            if start > 0 {
+                lines.remove(0);
+            }
+            let last = lines.into_iter().rev().find_map(|line| serde_json::from_slice(line).ok());
+            if last.is_some() {
+                return Ok(last);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Replace the file with `entries`, through a temporary file so that an
+    /// interrupted write does not lose the history.
+    fn rewrite(&self, entries: &[HistoryEntry]) -> std::io::Result<()> {
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        let temporary = Path::new(&temporary);
+
+        let mut file = File::create(temporary)?;
+        for entry in entries.iter() {
+            writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        }
+        file.sync_all()?;
+        std::fs::rename(temporary, &self.path)
+    }
+}
+
+/// Record a lookup in the history, if enabled. Failures are only logged, as
+/// they should not fail the lookup itself.
+pub async fn record(options: &GlobalOptions, command: &str, input: &str, result: &LinksAPIResult) {
+    let Some(history) = options.history() else {
+        return;
+    };
+    let (command, input, result) = (command.to_string(), input.to_string(), result.clone());
+    // Waiting for the lock of another process blocks.
+    let recorded =
+        tokio::task::spawn_blocking(move || history.record(&command, &input, &result)).await;
+    match recorded {
+        Ok(Ok(entry)) => log::debug!("Recorded the lookup as #{} in the history", entry.id),
+        Ok(Err(err)) => log::warn!("Failed to record the lookup in the history: {}", err),
+        Err(err) => log::warn!("Failed to record the lookup in the history: {}", err),
+    }
+}
+
+/// Record one of the many lookups made by `batch`, `cache warm`, `watch` or
+/// `compare`, only if the `bulk` setting of the history is enabled: they would
+/// push the lookups asked for one at a time out of the history otherwise.
+pub async fn record_bulk(
+    options: &GlobalOptions,
+    command: &str,
+    input: &str,
+    result: &LinksAPIResult,
+) {
+    if options.history_bulk {
+        record(options, command, input, result).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use odesli_rs::Platform;
+
+    use super::*;
+    use crate::testing::ResultBuilder;
+
+    /// A result for the Spotify song `id` with `title`, looked up in the UK.
+    fn result(id: &str, title: &str) -> LinksAPIResult {
+        let spotify = format!("SPOTIFY_SONG::{id}");
+        ResultBuilder::new(&spotify)
+            .link(Platform::Spotify, &spotify, &format!("https://open.spotify.com/track/{id}"))
+            .entity(&spotify, title, "Maroon 5")
+            .country("GB")
+            .build()
+    }
+
+    /// A history file of its own for `test`, removed.
+    fn history_path(test: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("odesli-history-{}-{}", test, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("history.jsonl")
+    }
+
+    fn ids(history: &History) -> Vec<u64> {
+        history.entries().unwrap().iter().map(|entry| entry.id).collect()
+    }
+
+    fn line_count(path: &Path) -> usize {
+        std::fs::read_to_string(path).unwrap().lines().count()
+    }
+
+    #[test]
+    fn entries_are_recorded_in_order() {
+        let path = history_path("order");
+        let history = History::new(path.clone(), 10, None);
+        assert!(history.entries().unwrap().is_empty());
+
+        let entry = history.record("get-url", " https://x ", &result("1", "Sugar")).unwrap();
+        assert_eq!((entry.id, entry.country.as_str()), (1, "GB"));
+        assert_eq!(entry.summary, "Sugar by Maroon 5");
+        history.record("get", "spotify:track:2", &result("2", "Memories")).unwrap();
+        assert_eq!(ids(&history), [1, 2]);
+        assert_eq!(history.get(2).unwrap().unwrap().input, "spotify:track:2");
+        assert!(history.get(3).unwrap().is_none());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn the_last_entries_are_kept() {
+        let path = history_path("retention");
+        let history = History::new(path.clone(), 10, None);
+        for index in 1..=11 {
+            history.record("get", &index.to_string(), &result("1", "Sugar")).unwrap();
+        }
+        // Listed within the limit, but not rewritten until a tenth more.
+        assert_eq!(ids(&history), (2..=11).collect::<Vec<_>>());
+        assert_eq!(line_count(&path), 11);
+
+        history.record("get", "12", &result("1", "Sugar")).unwrap();
+        assert_eq!(line_count(&path), 10);
+        assert_eq!(ids(&history), (3..=12).collect::<Vec<_>>());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn old_entries_are_dropped() {
+        let path = history_path("age");
+        let history = History::new(path.clone(), 10, Some(Duration::from_secs(3600)));
+        let mut old = history.record("get", "old", &result("1", "Sugar")).unwrap();
+        old.timestamp -= 2 * 3600;
+        std::fs::write(&path, format!("{}\n", serde_json::to_string(&old).unwrap())).unwrap();
+        assert!(history.entries().unwrap().is_empty());
+
+        history.record("get", "new", &result("1", "Sugar")).unwrap();
+        assert_eq!(line_count(&path), 1);
+        // The IDs go on from the dropped entries.
+        assert_eq!(ids(&history), [2]);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn ids_follow_the_last_entry() {
+        let path = history_path("ids");
+        let history = History::new(path.clone(), 10, None);
+        history.record("get", "1", &result("1", "Sugar")).unwrap();
+        // A line cut short by a crash, and a last entry longer than a chunk.
+        let mut contents = std::fs::read_to_string(&path).unwrap();
+        contents.push_str("{\"id\": 7, \"tim\n");
+        std::fs::write(&path, contents).unwrap();
+        let long = "x".repeat(2 * TAIL_CHUNK as usize);
+        history.record("get", &long, &result("1", "Sugar")).unwrap();
+        history.record("get", "3", &result("1", "Sugar")).unwrap();
+        assert_eq!(ids(&history), [1, 2, 3]);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn concurrent_lookups_are_all_recorded() {
+        let path = history_path("concurrent");
+        let threads = (0..8)
+            .map(|index| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    let history = History::new(path, 100, None);
+                    history.record("get", &index.to_string(), &result("1", "Sugar")).unwrap();
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(ids(&History::new(path.clone(), 100, None)), (1..=8).collect::<Vec<_>>());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn matches_all_the_words() {
+        let path = history_path("matches");
+        let history = History::new(path.clone(), 10, None);
+        let entry = history.record("get", "spotify:track:1", &result("1", "Sugar")).unwrap();
+        for query in ["sugar", "MAROON sugar", "open.spotify.com", "song.link/s/1", "track:1", ""] {
+            assert!(entry.matches(query), "{query}");
+        }
+        for query in ["memories", "sugar memories", "deezer"] {
+            assert!(!entry.matches(query), "{query}");
+        }
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
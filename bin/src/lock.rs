@@ -0,0 +1,60 @@
+//! Locks on the files shared by concurrent runs of the CLI, like the history.
+
+use std::{
+    fs::{File, OpenOptions},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use fs2::FileExt;
+
+/// How long to wait for another process to release a lock.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often to check whether a lock was released.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// An exclusive lock on a file, held as long as this value lives.
+///
+/// The lock is an advisory lock on a `<file>.lock` file next to it, which the
+/// system releases when the process exits, so a crash never leaves the file
+/// locked. The `.lock` file itself is kept: removing it would let another
+/// process lock a new file while this one is still locked.
+///
+/// Acquiring the lock may wait, so it must be done on a thread where blocking
+/// is allowed, e.g. within [`tokio::task::spawn_blocking`].
+#[derive(Debug)]
+pub struct FileLock {
+    _file: File,
+}
+
+impl FileLock {
+    /// Lock `path`, waiting for other processes holding it for up to a few
+    /// seconds.
+    pub fn acquire(path: &Path) -> std::io::Result<Self> {
+        let mut lock = path.to_path_buf().into_os_string();
+        lock.push(".lock");
+        let lock = PathBuf::from(lock);
+        if let Some(parent) = lock.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().write(true).create(true).truncate(false).open(&lock)?;
+
+        let started = Instant::now();
+        loop {
+            match file.try_lock_exclusive() {
+                Ok(()) => return Ok(Self { _file: file }),
+                Err(err) if err.kind() == fs2::lock_contended_error().kind() => {}
+                Err(err) => return Err(err),
+            }
+
+            if started.elapsed() > TIMEOUT {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("'{}' is locked by another process", path.display()),
+                ));
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
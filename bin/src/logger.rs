@@ -0,0 +1,33 @@
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Logs the messages of the CLI and of the library to stderr, as enabled by
+/// `-v` (debug) and `-vv` (trace). Warnings and errors are always logged.
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // Leave out the messages of the HTTP and SQLite crates.
+        metadata.target().starts_with("odesli")
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{}] {}", record.level().as_str().to_lowercase(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+pub fn init(verbosity: u8) {
+    let level = match verbosity {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
@@ -1,13 +1,53 @@
+pub mod batch;
+pub mod config;
+pub mod errors;
+pub mod history;
+pub mod lock;
+pub mod logger;
+pub mod options;
+pub mod output;
+pub mod select;
 pub mod subcommands;
+pub mod template;
+pub mod terminal;
+#[cfg(test)]
+mod testing;
 pub mod utils;
+pub mod validate;
+pub mod watch;
 
-use clap::{Arg, ArgAction, Command};
+use std::path::PathBuf;
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() {
+    let mut command = build_command();
+    let global_matches = command.clone().get_matches();
+    logger::init(global_matches.get_count("verbose"));
+
+    let options = match options::GlobalOptions::from_matches(&global_matches) {
+        Ok(options) => options,
+        Err(error) => {
+            // The format may come from the configuration that failed to load.
+            let json = global_matches.get_flag("json")
+                || global_matches
+                    .get_one::<output::OutputFormat>("format")
+                    .is_some_and(|format| format.is_structured());
+            errors::exit_with(error.as_ref(), json)
+        }
+    };
+
+    if let Err(error) = run(&mut command, &global_matches, &options).await {
+        errors::exit_with(error.as_ref(), options.format.is_structured());
+    }
+}
+
+fn build_command() -> Command {
     let mut command = Command::new("odesli")
         .about("interact with Odesli API using CLI")
         .version("0.1.0")
+        .after_help(errors::exit_codes_help())
         .subcommand_required(true)
         .arg_required_else_help(true)
         .arg(
@@ -19,11 +59,147 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .num_args(1)
                 .required(false),
         )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("Log the requests sent and their responses to stderr, -vv to include their bodies")
+                .action(ArgAction::Count)
+                .required(false),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .help("Print the requests that would be sent, with the API key redacted, without sending them")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .required(false),
+        )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .help(
+                    "The configuration file to use [default: $XDG_CONFIG_HOME/odesli/config.toml]",
+                )
+                .action(ArgAction::Set)
+                .value_parser(value_parser!(PathBuf))
+                .num_args(1)
+                .required(false),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .help("Use the settings of this [profile.<name>] of the configuration file")
+                .action(ArgAction::Set)
+                .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                .num_args(1)
+                .required(false),
+        )
+        .arg(
+            Arg::new("country")
+                .long("country")
+                .help("The country to get results for, e.g. US")
+                .action(ArgAction::Set)
+                .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                .num_args(1)
+                .required(false),
+        )
         .arg(
             Arg::new("json")
                 .short('j')
                 .long("json")
-                .help("Dump the output in JSON as received from API")
+                .help("Dump the output in JSON as received from API, same as --format json")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .conflicts_with("format")
+                .required(false),
+        )
+        .arg(
+            Arg::new("format")
+                .short('f')
+                .long("format")
+                .help("The format to print the output in, csv, tsv and markdown only for results")
+                .action(ArgAction::Set)
+                .value_parser(value_parser!(output::OutputFormat))
+                .num_args(1)
+                .required(false),
+        )
+        .arg(
+            Arg::new("color")
+                .long("color")
+                .help("When to color the output, colors are also disabled by a non-empty NO_COLOR")
+                .action(ArgAction::Set)
+                .value_parser(value_parser!(terminal::ColorChoice))
+                .default_value("auto")
+                .num_args(1)
+                .required(false),
+        )
+        .arg(
+            Arg::new("template")
+                .short('t')
+                .long("template")
+                .help("Render results with this template, e.g. \"{title} by {artist}: {page_url}\"")
+                .action(ArgAction::Set)
+                .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                .num_args(1)
+                .conflicts_with_all(["json", "format"])
+                .required(false),
+        )
+        .arg(
+            Arg::new("template-file")
+                .long("template-file")
+                .help("Render results with the template in this file")
+                .action(ArgAction::Set)
+                .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                .num_args(1)
+                .conflicts_with_all(["json", "format", "template"])
+                .required(false),
+        )
+        .arg(
+            Arg::new("fields")
+                .long("fields")
+                .visible_alias("select")
+                .help(
+                    "Only print these comma separated fields of the results, \
+                     e.g. links.spotify.url,entities.*.title",
+                )
+                .action(ArgAction::Set)
+                .value_parser(|fields: &str| fields.parse::<select::FieldSelection>())
+                .num_args(1)
+                .conflicts_with_all(["template", "template-file"])
+                .required(false),
+        )
+        .arg(
+            Arg::new("catalog")
+                .long("catalog")
+                .help("Record lookups in the catalog of looked up IDs at this path")
+                .action(ArgAction::Set)
+                .value_parser(value_parser!(PathBuf))
+                .num_args(1)
+                .required(false),
+        )
+        .arg(
+            Arg::new("no-catalog")
+                .long("no-catalog")
+                .help("Do not use the catalog, even if the configuration enables it")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .conflicts_with("catalog")
+                .required(false),
+        )
+        .arg(
+            Arg::new("prefer-catalog")
+                .long("prefer-catalog")
+                .help("Answer lookups by ID from the catalog when possible, enabling it")
+                .num_args(0)
+                .action(ArgAction::SetTrue)
+                .conflicts_with("no-catalog")
+                .required(false),
+        )
+        .arg(
+            Arg::new("no-history")
+                .long("no-history")
+                .help("Do not record lookups in the history")
                 .num_args(0)
                 .action(ArgAction::SetTrue)
                 .required(false),
@@ -32,23 +208,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     for subcommand in crate::subcommands::SUBCOMMANDS.iter() {
         command = command.subcommand(subcommand.get_subcommand())
     }
+    command
+}
 
-    let global_matches = command.clone().get_matches();
-
-    let api_key = if global_matches.contains_id("api-key") {
-        Some(global_matches.get_one::<String>("api-key").expect("contains_id").to_string())
-    } else {
-        None
-    };
-    let dump_json = global_matches.get_flag("json");
-
+async fn run(
+    command: &mut Command,
+    global_matches: &ArgMatches,
+    options: &options::GlobalOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
     match global_matches.subcommand() {
         Some((cmd, cmd_matches)) => {
             for subcommand in subcommands::SUBCOMMANDS.iter() {
                 if cmd.eq(subcommand.name()) {
-                    subcommand
-                        .handle_subcommand(&mut command, cmd_matches, api_key, dump_json)
-                        .await?;
+                    subcommand.handle_subcommand(command, cmd_matches, options).await?;
                     return Ok(());
                 }
             }
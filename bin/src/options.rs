@@ -0,0 +1,345 @@
+use std::{path::PathBuf, time::Duration};
+
+use clap::{ArgMatches, ValueEnum};
+use odesli_rs::{
+    ApiKey, Catalog, OdesliError, Platform, API_KEY_COMMAND_ENV, API_KEY_ENV, API_KEY_FILE_ENV,
+};
+
+use crate::{
+    config::{CacheSettings, Config, HistorySettings, Settings, WatchSettings},
+    history::History,
+    output::OutputFormat,
+    select::FieldSelection,
+    template::Template,
+    terminal::{ColorChoice, Terminal},
+};
+
+/// A place to get the API key from.
+#[derive(Clone, Debug)]
+pub enum ApiKeySource {
+    /// The key itself, from `--api-key` or the configuration file.
+    Key(ApiKey),
+    /// The `ODESLI_API_KEY*` environment variables, see
+    /// [`odesli_rs::api_key_from_env`].
+    Env,
+    /// A key file, from the configuration file.
+    File(PathBuf),
+    /// A command printing the key, from the configuration file.
+    Command(String),
+}
+
+impl std::fmt::Display for ApiKeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Key(_) => write!(f, "the command line or the configuration file"),
+            Self::Env => write!(f, "the environment"),
+            Self::File(path) => write!(f, "the file '{}'", path.display()),
+            Self::Command(command) => write!(f, "the command '{}'", command),
+        }
+    }
+}
+
+/// Options shared by all the subcommands, parsed from the global arguments
+/// and the configuration file, the former taking precedence.
+pub struct GlobalOptions {
+    /// The configuration file in use, if any.
+    pub config_path: Option<PathBuf>,
+    /// The profile of the configuration file in use, if any.
+    pub profile: Option<String>,
+    /// Where to get the Odesli API key from, in order of precedence. The
+    /// first source giving a key is used.
+    pub api_key_sources: Vec<ApiKeySource>,
+    /// The country to get results for, Odesli's default (US) if `None`.
+    pub country: Option<String>,
+    /// Whether `country` was given with `--country`, rather than by the
+    /// configuration.
+    pub country_from_flag: bool,
+    /// The URL of the API, [`odesli_rs::BASE_URL`] if `None`.
+    pub base_url: Option<String>,
+    /// Platforms to list first when pretty printing results.
+    pub preferred_platforms: Vec<Platform>,
+    /// The format to print results in.
+    pub format: OutputFormat,
+    /// Render results with this template instead of printing them in `format`.
+    pub template: Option<Template>,
+    /// Only print these fields of the results, in `format`.
+    pub fields: Option<FieldSelection>,
+    /// What the terminal supports when pretty printing results.
+    pub terminal: Terminal,
+    /// Where the catalog of looked up IDs is stored, `None` if disabled.
+    pub catalog_path: Option<PathBuf>,
+    /// Serve lookups by ID from the catalog when possible.
+    pub prefer_catalog: bool,
+    /// Ignore catalog entries older than this when serving lookups from it.
+    pub catalog_max_age: Option<Duration>,
+    /// Print the requests that would be sent instead of sending them.
+    pub dry_run: bool,
+    /// Where the history of lookups is stored, `None` if disabled.
+    pub history_path: Option<PathBuf>,
+    /// Keep at most this many lookups in the history.
+    pub history_max_entries: usize,
+    /// Drop the lookups older than this from the history.
+    pub history_max_age: Option<Duration>,
+    /// Also record the lookups of `batch`, `cache warm`, `watch` and
+    /// `compare` in the history.
+    pub history_bulk: bool,
+    /// Where the last results of the watched links are stored.
+    pub watch_path: Option<PathBuf>,
+    /// How long `watch` waits between checks, unless `--interval` is given.
+    pub watch_interval: Option<Duration>,
+    /// Where `watch` POSTs the changes, unless `--webhook` is given.
+    pub watch_webhook: Option<String>,
+}
+
+impl GlobalOptions {
+    pub fn from_matches(matches: &ArgMatches) -> Result<Self, Box<dyn std::error::Error>> {
+        let config = Config::load(matches.get_one::<PathBuf>("config").map(PathBuf::as_path))?;
+        if let Some(path) = config.path.as_ref() {
+            log::debug!("Loaded the configuration from {}", path.display());
+        }
+        let profile = matches.get_one::<String>("profile").cloned();
+        let settings = config.settings(profile.as_deref())?;
+
+        let prefer_catalog =
+            matches.get_flag("prefer-catalog") || settings.cache.prefer.unwrap_or_default();
+        // The catalog is opt-in: it is only used when enabled, given a path,
+        // or preferred.
+        let catalog_path = if matches.get_flag("no-catalog") {
+            None
+        } else if let Some(path) = matches.get_one::<PathBuf>("catalog") {
+            Some(path.clone())
+        } else if settings.cache.enabled == Some(false) {
+            None
+        } else if settings.cache.enabled == Some(true)
+            || settings.cache.path.is_some()
+            || prefer_catalog
+        {
+            settings.cache.path.clone().or_else(default_catalog_path)
+        } else {
+            None
+        };
+
+        let history_path =
+            if matches.get_flag("no-history") || settings.history.enabled == Some(false) {
+                None
+            } else {
+                settings.history.path.clone().or_else(default_history_path)
+            };
+
+        let template = if let Some(template) = matches.get_one::<String>("template") {
+            Some(Template::from_inline(template)?)
+        } else if let Some(path) = matches.get_one::<String>("template-file") {
+            Some(Template::from_file(path)?)
+        } else {
+            None
+        };
+
+        let format = if matches.get_flag("json") {
+            OutputFormat::Json
+        } else if let Some(format) = matches.get_one::<OutputFormat>("format") {
+            *format
+        } else if let Some(format) = settings.format.as_ref() {
+            OutputFormat::from_str(format, true)
+                .map_err(|_| format!("Invalid format '{format}' in the configuration"))?
+        } else {
+            OutputFormat::default()
+        };
+
+        let preferred_platforms = settings
+            .preferred_platforms
+            .iter()
+            .flatten()
+            .map(|platform| {
+                platform
+                    .parse::<Platform>()
+                    .map_err(|_| format!("Unknown platform '{platform}' in the configuration"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let catalog_max_age = match settings.cache.max_age.as_ref() {
+            Some(max_age) => Some(crate::utils::parse_duration(max_age)?),
+            None => None,
+        };
+        let history_max_age = match settings.history.max_age.as_ref() {
+            Some(max_age) => Some(crate::utils::parse_duration(max_age)?),
+            None => None,
+        };
+        let watch_interval = match settings.watch.interval.as_ref() {
+            Some(interval) => Some(crate::utils::parse_duration(interval)?),
+            None => None,
+        };
+
+        // The environment overrides the configuration file, but not the flag.
+        let mut api_key_sources = Vec::new();
+        if let Some(key) = matches.get_one::<String>("api-key") {
+            api_key_sources.push(ApiKeySource::Key(key.as_str().into()));
+        }
+        api_key_sources.push(ApiKeySource::Env);
+        if let Some(key) = settings.api_key {
+            api_key_sources.push(ApiKeySource::Key(key.into()));
+        }
+        if let Some(path) = settings.api_key_file.clone() {
+            api_key_sources.push(ApiKeySource::File(path));
+        }
+        if let Some(command) = settings.api_key_command.clone() {
+            api_key_sources.push(ApiKeySource::Command(command));
+        }
+
+        Ok(Self {
+            config_path: config.path,
+            profile,
+            api_key_sources,
+            country: matches.get_one::<String>("country").cloned().or(settings.country),
+            country_from_flag: matches.contains_id("country"),
+            base_url: settings.base_url,
+            preferred_platforms,
+            format,
+            catalog_path,
+            template,
+            fields: matches.get_one::<FieldSelection>("fields").cloned(),
+            terminal: Terminal::detect(
+                matches.get_one::<ColorChoice>("color").copied().unwrap_or_default(),
+            ),
+            prefer_catalog,
+            catalog_max_age,
+            dry_run: matches.get_flag("dry-run"),
+            history_path,
+            history_max_entries: settings
+                .history
+                .max_entries
+                .unwrap_or(crate::history::DEFAULT_MAX_ENTRIES),
+            history_max_age,
+            history_bulk: settings.history.bulk.unwrap_or(false),
+            watch_path: settings.watch.path.or_else(default_watch_path),
+            watch_interval,
+            watch_webhook: settings.watch.webhook,
+        })
+    }
+
+    /// The settings in effect, as they would be written in the configuration
+    /// file. The API key is redacted.
+    pub fn effective_settings(&self) -> Settings {
+        Settings {
+            api_key: self
+                .api_key_sources
+                .iter()
+                .any(|source| matches!(source, ApiKeySource::Key(_)))
+                .then(|| "<redacted>".to_string()),
+            api_key_file: self.api_key_sources.iter().find_map(|source| match source {
+                ApiKeySource::File(path) => Some(path.clone()),
+                _ => None,
+            }),
+            api_key_command: self.api_key_sources.iter().find_map(|source| match source {
+                ApiKeySource::Command(command) => Some(command.clone()),
+                _ => None,
+            }),
+            country: self.country.clone(),
+            format: self.format.to_possible_value().map(|format| format.get_name().to_string()),
+            preferred_platforms: Some(
+                self.preferred_platforms
+                    .iter()
+                    .map(|platform| platform.as_str().to_string())
+                    .collect(),
+            ),
+            base_url: Some(
+                self.base_url.clone().unwrap_or_else(|| odesli_rs::BASE_URL.to_string()),
+            ),
+            cache: CacheSettings {
+                enabled: Some(self.catalog_path.is_some()),
+                path: self.catalog_path.clone(),
+                prefer: Some(self.prefer_catalog),
+                max_age: self.catalog_max_age.map(crate::utils::format_duration),
+            },
+            history: HistorySettings {
+                enabled: Some(self.history_path.is_some()),
+                path: self.history_path.clone(),
+                max_entries: Some(self.history_max_entries),
+                max_age: self.history_max_age.map(crate::utils::format_duration),
+                bulk: Some(self.history_bulk),
+            },
+            watch: WatchSettings {
+                path: self.watch_path.clone(),
+                interval: self.watch_interval.map(crate::utils::format_duration),
+                webhook: self.watch_webhook.clone(),
+            },
+        }
+    }
+
+    /// Get the API key from the first of the `api_key_sources` giving one.
+    ///
+    /// This may read a file or run a command, so it is only done when a client
+    /// is needed.
+    pub fn resolve_api_key(&self) -> Result<Option<ApiKey>, OdesliError> {
+        for source in self.api_key_sources.iter() {
+            let key = match source {
+                ApiKeySource::Key(key) => Some(key.clone()),
+                ApiKeySource::Env => odesli_rs::api_key_from_env()?,
+                ApiKeySource::File(path) => Some(odesli_rs::read_api_key_file(path)?),
+                ApiKeySource::Command(command) => Some(odesli_rs::api_key_from_command(command)?),
+            };
+            if key.is_some() {
+                log::debug!("Using the API key from {}", source);
+                return Ok(key);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Whether an API key is configured, without resolving it.
+    pub fn has_api_key(&self) -> bool {
+        self.api_key_sources.iter().any(|source| match source {
+            ApiKeySource::Env => [API_KEY_ENV, API_KEY_FILE_ENV, API_KEY_COMMAND_ENV]
+                .iter()
+                .any(|name| std::env::var_os(name).is_some_and(|value| !value.is_empty())),
+            _ => true,
+        })
+    }
+
+    /// Whether results are pretty printed for humans, rather than in a format
+    /// meant for other programs.
+    pub fn pretty_printing(&self) -> bool {
+        self.format == OutputFormat::Text && self.template.is_none() && self.fields.is_none()
+    }
+
+    /// Open the catalog, creating it if it does not exist yet.
+    pub fn open_catalog(&self) -> Result<Option<Catalog>, OdesliError> {
+        let Some(path) = self.catalog_path.as_ref() else {
+            return Ok(None);
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(OdesliError::IoError)?;
+        }
+        Catalog::open(path).map(Some)
+    }
+
+    /// Open the catalog for the subcommands that need it, failing if it is
+    /// disabled.
+    pub fn require_catalog(&self) -> Result<Catalog, Box<dyn std::error::Error>> {
+        self.open_catalog()?.ok_or_else(|| {
+            "The catalog is disabled, set enabled = true in the [cache] section of the \
+             configuration or give --catalog"
+                .into()
+        })
+    }
+
+    /// The history of lookups, `None` if disabled.
+    pub fn history(&self) -> Option<History> {
+        let path = self.history_path.clone()?;
+        Some(History::new(path, self.history_max_entries, self.history_max_age))
+    }
+}
+
+/// `$XDG_DATA_HOME/odesli/history.jsonl` (or the platform equivalent).
+fn default_history_path() -> Option<PathBuf> {
+    Some(dirs::data_dir()?.join("odesli").join("history.jsonl"))
+}
+
+/// `$XDG_DATA_HOME/odesli/watch.json` (or the platform equivalent).
+fn default_watch_path() -> Option<PathBuf> {
+    Some(dirs::data_dir()?.join("odesli").join("watch.json"))
+}
+
+/// `$XDG_DATA_HOME/odesli/catalog.sqlite3` (or the platform equivalent).
+fn default_catalog_path() -> Option<PathBuf> {
+    Some(dirs::data_dir()?.join("odesli").join("catalog.sqlite3"))
+}
@@ -0,0 +1,203 @@
+use clap::ValueEnum;
+use odesli_rs::{LinksAPIResult, Platform};
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+use crate::{errors::ExitError, options::GlobalOptions};
+
+/// The formats results can be printed in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable summary.
+    #[default]
+    Text,
+    /// Pretty printed JSON, as received from the API.
+    Json,
+    /// JSON on a single line.
+    Ndjson,
+    /// YAML, with the same structure as the JSON.
+    Yaml,
+    /// One row per platform, comma separated.
+    Csv,
+    /// One row per platform, tab separated.
+    Tsv,
+    /// One row per platform, as a Markdown table.
+    Markdown,
+}
+
+/// Columns of the tabular formats, in order.
+pub const TABLE_COLUMNS: [&str; 7] = [
+    "platform",
+    "entity_unique_id",
+    "title",
+    "artist",
+    "url",
+    "native_app_uri_mobile",
+    "native_app_uri_desktop",
+];
+
+impl OutputFormat {
+    /// Whether this format is a serialization of the data (JSON or YAML).
+    pub fn is_structured(&self) -> bool {
+        matches!(self, Self::Json | Self::Ndjson | Self::Yaml)
+    }
+
+    /// Serialize `value` in this format, which must be a structured one.
+    pub fn render_value<T: Serialize + ?Sized>(
+        &self,
+        value: &T,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(match self {
+            Self::Json => serde_json::to_string_pretty(value)?,
+            Self::Ndjson => serde_json::to_string(value)?,
+            Self::Yaml => serde_yaml::to_string(value)?.trim_end().to_string(),
+            _ => unreachable!("{:?} is not a structured format", self),
+        })
+    }
+
+    /// Whether this format prints one row per platform.
+    pub fn is_tabular(&self) -> bool {
+        matches!(self, Self::Csv | Self::Tsv | Self::Markdown)
+    }
+
+    /// Fail if this format is tabular, for `command` whose output is not a
+    /// result and so has no rows per platform.
+    pub fn reject_tabular(&self, command: &str) -> Result<(), ExitError> {
+        if !self.is_tabular() {
+            return Ok(());
+        }
+        let name = self.to_possible_value().expect("no skipped variants");
+        Err(ExitError::new(
+            "badInput",
+            format!(
+                "The {} format is not supported by {}, use text, json, ndjson or yaml",
+                name.get_name(),
+                command
+            ),
+        ))
+    }
+
+    /// Format the header of a table with `columns`.
+    pub fn format_header(&self, columns: &[&str]) -> String {
+        let columns = columns.iter().map(|column| column.to_string()).collect::<Vec<_>>();
+        let header = self.format_row(&columns);
+        match self {
+            Self::Markdown => {
+                format!("{}\n|{}", header, columns.iter().map(|_| "---|").collect::<String>())
+            }
+            _ => header,
+        }
+    }
+
+    /// Format a row of a table, escaping the cells as needed.
+    pub fn format_row(&self, cells: &[String]) -> String {
+        match self {
+            Self::Csv => cells.iter().map(|cell| escape_csv(cell)).collect::<Vec<_>>().join(","),
+            Self::Tsv => cells
+                .iter()
+                .map(|cell| cell.replace(['\t', '\n', '\r'], " "))
+                .collect::<Vec<_>>()
+                .join("\t"),
+            Self::Markdown => format!(
+                "| {} |",
+                cells
+                    .iter()
+                    .map(|cell| cell.replace('|', "\\|").replace(['\n', '\r'], " "))
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            ),
+            _ => unreachable!("{:?} is not a tabular format", self),
+        }
+    }
+
+    /// Render `result` in this format. [`OutputFormat::Text`] is handled by
+    /// [`crate::terminal::print_result`] instead.
+    pub fn render(&self, result: &LinksAPIResult) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(match self {
+            Self::Text => unreachable!("text output is pretty printed"),
+            Self::Json | Self::Ndjson | Self::Yaml => self.render_value(result)?,
+            Self::Csv | Self::Tsv | Self::Markdown => {
+                let mut lines = vec![self.format_header(&TABLE_COLUMNS)];
+                lines.extend(table_rows(result).iter().map(|row| self.format_row(row)));
+                lines.join("\n")
+            }
+        })
+    }
+}
+
+/// Print `result` as requested by the global options: with the template, the
+/// field selection or the format, in that order of precedence.
+/// `print_text_header` is called before pretty printing the result in the text
+/// format.
+pub fn print_result(
+    options: &GlobalOptions,
+    result: &LinksAPIResult,
+    print_text_header: impl FnOnce(),
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(template) = options.template.as_ref() {
+        print_rendered(&template.render(result)?);
+    } else if let Some(fields) = options.fields.as_ref() {
+        let value = serde_json::to_value(result)?;
+        match options.format {
+            OutputFormat::Text => {
+                for cell in fields.cells(&value, "\n") {
+                    println!("{}", cell);
+                }
+            }
+            format if format.is_structured() => {
+                println!("{}", format.render_value(&fields.project(&value))?)
+            }
+            format => {
+                println!("{}", format.format_header(&fields.columns()));
+                println!("{}", format.format_row(&fields.cells(&value, ";")));
+            }
+        }
+    } else if options.format == OutputFormat::Text {
+        print_text_header();
+        crate::terminal::print_result(&options.terminal, result, &options.preferred_platforms);
+    } else {
+        println!("{}", options.format.render(result)?);
+    }
+    Ok(())
+}
+
+/// Print the output of a template, ending it with a newline if it has none.
+pub fn print_rendered(rendered: &str) {
+    if rendered.ends_with('\n') {
+        print!("{}", rendered);
+    } else {
+        println!("{}", rendered);
+    }
+}
+
+/// Flatten `result` into one row per platform, with the cells in the order of
+/// [`TABLE_COLUMNS`].
+///
+/// Rows are in the order of [`Platform::iter`], so that the output is the same
+/// across runs.
+pub fn table_rows(result: &LinksAPIResult) -> Vec<Vec<String>> {
+    Platform::iter()
+        .filter_map(|platform| {
+            let link = result.get_platform_url(&platform)?;
+            let entity = result.get_platform_entity(&platform);
+
+            Some(vec![
+                platform.as_str().to_string(),
+                link.entity_unique_id.clone(),
+                entity.and_then(|entity| entity.title.clone()).unwrap_or_default(),
+                entity.and_then(|entity| entity.artist_name.clone()).unwrap_or_default(),
+                link.url.clone(),
+                link.native_app_uri_mobile.clone().unwrap_or_default(),
+                link.native_app_uri_desktop.clone().unwrap_or_default(),
+            ])
+        })
+        .collect()
+}
+
+fn escape_csv(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
@@ -0,0 +1,224 @@
+use std::str::FromStr;
+
+use serde_json::Value;
+
+/// A path into the JSON of a result, e.g. `links.spotify.url` or
+/// `entities.*.title`.
+///
+/// Segments are separated by dots and `*` matches every key (or element).
+/// `links` and `entities` are short for `linksByPlatform` and
+/// `entitiesByUniqueId`, and snake_case keys are accepted for camelCase ones.
+#[derive(Clone, Debug)]
+pub struct FieldPath {
+    path: String,
+    segments: Vec<String>,
+}
+
+impl FromStr for FieldPath {
+    type Err = String;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let path = path.trim();
+        if path.is_empty() {
+            return Err("Empty field path".to_string());
+        }
+        let segments = path.split('.').map(String::from).collect::<Vec<_>>();
+        if segments.iter().any(|segment| segment.is_empty()) {
+            return Err(format!("Invalid field path '{path}'"));
+        }
+        Ok(Self { path: path.to_string(), segments })
+    }
+}
+
+impl FieldPath {
+    pub fn as_str(&self) -> &str {
+        &self.path
+    }
+
+    /// Whether this path may match more than one value.
+    pub fn has_wildcard(&self) -> bool {
+        self.segments.iter().any(|segment| segment == "*")
+    }
+
+    /// All the values this path matches in `value`.
+    pub fn select<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        let mut current = vec![value];
+        for segment in self.segments.iter() {
+            current = current
+                .into_iter()
+                .flat_map(|value| -> Vec<&'a Value> {
+                    match (value, segment.as_str()) {
+                        (Value::Object(map), "*") => map.values().collect(),
+                        (Value::Array(values), "*") => values.iter().collect(),
+                        (Value::Object(map), key) => lookup_key(map, key).into_iter().collect(),
+                        (Value::Array(values), index) => index
+                            .parse::<usize>()
+                            .ok()
+                            .and_then(|index| values.get(index))
+                            .into_iter()
+                            .collect(),
+                        _ => Vec::new(),
+                    }
+                })
+                .collect();
+        }
+        current
+    }
+}
+
+fn lookup_key<'a>(map: &'a serde_json::Map<String, Value>, key: &str) -> Option<&'a Value> {
+    let alias = match key {
+        "links" => "linksByPlatform",
+        "entities" => "entitiesByUniqueId",
+        _ => key,
+    };
+    map.get(alias).or_else(|| map.get(&snake_to_camel_case(alias)))
+}
+
+fn snake_to_camel_case(key: &str) -> String {
+    let mut parts = key.split('_');
+    let mut camel = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            camel.extend(first.to_uppercase());
+            camel.push_str(chars.as_str());
+        }
+    }
+    camel
+}
+
+/// A list of [`FieldPath`]s to project results onto, as given to `--fields`.
+#[derive(Clone, Debug)]
+pub struct FieldSelection {
+    pub paths: Vec<FieldPath>,
+}
+
+impl FromStr for FieldSelection {
+    type Err = String;
+
+    fn from_str(fields: &str) -> Result<Self, Self::Err> {
+        Ok(Self { paths: fields.split(',').map(FieldPath::from_str).collect::<Result<_, _>>()? })
+    }
+}
+
+impl FieldSelection {
+    /// Project `value` onto the selected fields: an object from each path to
+    /// the value it matches, `null` if none. Paths with a wildcard map to the
+    /// array of all the values they match.
+    pub fn project(&self, value: &Value) -> Value {
+        let mut projection = serde_json::Map::new();
+        for path in self.paths.iter() {
+            let values = path.select(value);
+            let selected = if path.has_wildcard() {
+                Value::Array(values.into_iter().cloned().collect())
+            } else {
+                values.first().map(|value| (*value).clone()).unwrap_or(Value::Null)
+            };
+            projection.insert(path.as_str().to_string(), selected);
+        }
+        Value::Object(projection)
+    }
+
+    /// The selected values as plain text cells, one per path. Multiple values
+    /// are separated by `separator`.
+    pub fn cells(&self, value: &Value, separator: &str) -> Vec<String> {
+        self.paths
+            .iter()
+            .map(|path| {
+                path.select(value).into_iter().map(plain_text).collect::<Vec<_>>().join(separator)
+            })
+            .collect()
+    }
+
+    /// The names of the selected fields, for table headers.
+    pub fn columns(&self) -> Vec<&str> {
+        self.paths.iter().map(FieldPath::as_str).collect()
+    }
+}
+
+/// Strings as they are, anything else as JSON.
+pub fn plain_text(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn result() -> Value {
+        json!({
+            "entityUniqueId": "SPOTIFY_SONG::1",
+            "linksByPlatform": {
+                "spotify": { "url": "https://spotify.com/1" },
+                "deezer": { "url": "https://deezer.com/2" },
+            },
+            "entitiesByUniqueId": {
+                "SPOTIFY_SONG::1": { "title": "Sugar", "platforms": ["spotify", "appleMusic"] },
+            },
+        })
+    }
+
+    fn select(path: &str) -> Vec<Value> {
+        FieldPath::from_str(path).unwrap().select(&result()).into_iter().cloned().collect()
+    }
+
+    #[test]
+    fn paths() {
+        assert_eq!(select("links.spotify.url"), [json!("https://spotify.com/1")]);
+        assert_eq!(select("entity_unique_id"), [json!("SPOTIFY_SONG::1")]);
+        assert_eq!(select("entities.*.platforms.1"), [json!("appleMusic")]);
+        assert!(select("links.tidal.url").is_empty());
+        assert!(select("entity_unique_id.nested").is_empty());
+    }
+
+    #[test]
+    fn wildcards() {
+        assert_eq!(
+            select("links.*.url"),
+            [json!("https://spotify.com/1"), json!("https://deezer.com/2")]
+        );
+        assert_eq!(select("entities.*.platforms.*"), [json!("spotify"), json!("appleMusic")]);
+    }
+
+    #[test]
+    fn invalid_paths() {
+        for path in ["", " ", "links..url", ".links", "links."] {
+            assert!(FieldPath::from_str(path).is_err(), "{path:?}");
+        }
+        assert!(FieldSelection::from_str("title,").is_err());
+    }
+
+    #[test]
+    fn projection() {
+        let selection =
+            FieldSelection::from_str("links.spotify.url,links.*.url,links.tidal.url").unwrap();
+        assert_eq!(
+            selection.project(&result()),
+            json!({
+                "links.spotify.url": "https://spotify.com/1",
+                "links.*.url": ["https://spotify.com/1", "https://deezer.com/2"],
+                "links.tidal.url": null,
+            })
+        );
+        // A wildcard matching nothing is an empty array rather than null.
+        let selection = FieldSelection::from_str("links.tidal.*").unwrap();
+        assert_eq!(selection.project(&result()), json!({ "links.tidal.*": [] }));
+    }
+
+    #[test]
+    fn cells() {
+        let selection = FieldSelection::from_str("entity_unique_id,links.*.url,entities").unwrap();
+        assert_eq!(selection.columns(), ["entity_unique_id", "links.*.url", "entities"]);
+        let cells = selection.cells(&result(), ";");
+        assert_eq!(cells[..2], ["SPOTIFY_SONG::1", "https://spotify.com/1;https://deezer.com/2"]);
+        // Values that are not strings are printed as JSON.
+        assert!(cells[2].starts_with(r#"{"SPOTIFY_SONG::1":"#), "{}", cells[2]);
+    }
+}
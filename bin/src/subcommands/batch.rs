@@ -0,0 +1,272 @@
+use std::{
+    fs::OpenOptions,
+    io::{Read, Write},
+    path::PathBuf,
+};
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+
+use super::OdesliSubcommand;
+use crate::{
+    batch::{BatchInput, Journal, RateLimiter},
+    options::GlobalOptions,
+    output::{table_rows, OutputFormat, TABLE_COLUMNS},
+};
+
+/// Requests per minute allowed by Odesli without an API key.
+pub const DEFAULT_RATE_LIMIT: u32 = 10;
+
+pub struct BatchSubcommand;
+
+#[async_trait::async_trait]
+impl OdesliSubcommand for BatchSubcommand {
+    fn name(&self) -> &'static str {
+        "batch"
+    }
+
+    fn get_subcommand(&self) -> Command {
+        Command::new(self.name())
+            .about("find matches for many URLs or IDs, printing one JSON line per input by default")
+            .arg(
+                Arg::new("input")
+                    .help("File with one URL or platform:type:id per line, '-' for stdin")
+                    .action(ArgAction::Set)
+                    .default_value("-")
+                    .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("concurrency")
+                    .short('c')
+                    .long("concurrency")
+                    .help("The maximum number of requests in flight")
+                    .action(ArgAction::Set)
+                    .default_value("4")
+                    .value_parser(value_parser!(usize))
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("rate-limit")
+                    .short('r')
+                    .long("rate-limit")
+                    .help(
+                        "The maximum number of requests per minute, 0 for no limit \
+                         [default: 10 without an API key, no limit with one]",
+                    )
+                    .action(ArgAction::Set)
+                    .value_parser(value_parser!(u32))
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("output")
+                    .short('o')
+                    .long("output")
+                    .help("Write the JSON lines to this file instead of stdout")
+                    .action(ArgAction::Set)
+                    .value_parser(value_parser!(PathBuf))
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("journal")
+                    .long("journal")
+                    .help(
+                        "Record the processed inputs in this file, only printing failures \
+                         to stderr [default: <output>.journal when --output is set]",
+                    )
+                    .action(ArgAction::Set)
+                    .value_parser(value_parser!(PathBuf))
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("resume")
+                    .long("resume")
+                    .help(
+                        "Skip the inputs the journal records as succeeded, retry the rest \
+                         and append to the output",
+                    )
+                    .num_args(0)
+                    .action(ArgAction::SetTrue),
+            )
+    }
+
+    async fn handle_subcommand(
+        &self,
+        _final_cmd: &mut Command,
+        cmd_matches: &ArgMatches,
+        options: &GlobalOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let input = cmd_matches.get_one::<String>("input").expect("Argument 'input' has a default");
+        let concurrency = *cmd_matches
+            .get_one::<usize>("concurrency")
+            .expect("Argument 'concurrency' has a default");
+        let rate_limit = match cmd_matches.get_one::<u32>("rate-limit") {
+            Some(rate_limit) => *rate_limit,
+            None if !options.has_api_key() => DEFAULT_RATE_LIMIT,
+            None => 0,
+        };
+
+        let output_path = cmd_matches.get_one::<PathBuf>("output");
+        let journal_path = cmd_matches.get_one::<PathBuf>("journal").cloned().or_else(|| {
+            output_path.map(|output_path| {
+                let mut journal_path = output_path.clone().into_os_string();
+                journal_path.push(".journal");
+                PathBuf::from(journal_path)
+            })
+        });
+        let resume = cmd_matches.get_flag("resume");
+        if resume && journal_path.is_none() {
+            return Err("--resume requires --journal or --output".into());
+        }
+
+        let succeeded = match journal_path.as_ref() {
+            Some(journal_path) if resume => Journal::succeeded_inputs(journal_path)?,
+            _ => Default::default(),
+        };
+        let lines = read_lines(input)?;
+        let inputs = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| !succeeded.contains(line.trim()))
+            .filter_map(|(index, line)| Some((index, BatchInput::parse(line)?)))
+            .collect::<Vec<_>>();
+
+        let client = crate::utils::build_odesli_client(options)?;
+        if options.dry_run {
+            for (_, input) in inputs.iter() {
+                crate::utils::print_dry_run(input.request_url(&client)?);
+            }
+            return Ok(());
+        }
+
+        let mut journal = journal_path
+            .as_ref()
+            .map(|journal_path| Journal::open(journal_path, resume))
+            .transpose()?;
+        // When appending to a previous run's output, its table header is reused.
+        let appending = resume
+            && output_path.is_some_and(|output_path| {
+                std::fs::metadata(output_path).is_ok_and(|metadata| metadata.len() > 0)
+            });
+        let mut output: Box<dyn Write + Send> = match output_path {
+            Some(output_path) => Box::new(
+                OpenOptions::new()
+                    .create(true)
+                    .write(true)
+                    .append(resume)
+                    .truncate(!resume)
+                    .open(output_path)?,
+            ),
+            None => Box::new(std::io::stdout()),
+        };
+
+        let rate_limiter = (rate_limit > 0).then(|| RateLimiter::per_minute(rate_limit));
+        let mut outcomes = crate::batch::resolve_all(client, inputs, concurrency, rate_limiter);
+
+        let format = options.format;
+        if format.is_tabular() && options.template.is_none() && !appending {
+            let mut columns = vec!["line", "input"];
+            match options.fields.as_ref() {
+                Some(fields) => columns.extend(fields.columns()),
+                None => columns.extend(TABLE_COLUMNS),
+            }
+            columns.push("error");
+            writeln!(output, "{}", format.format_header(&columns))?;
+        }
+
+        while let Some((index, outcome)) = outcomes.recv().await {
+            let input = lines[index].trim();
+            let succeeded = outcome.is_ok();
+            if let Ok(result) = outcome.as_ref() {
+                crate::history::record_bulk(options, self.name(), input, result).await;
+            }
+
+            // With a journal, failures are left out of the output so that
+            // retrying them with --resume does not list them twice.
+            if let (Some(journal), Err(error)) = (journal.as_mut(), outcome.as_ref()) {
+                eprintln!("Failed to get results for '{}': {}", input, error);
+                journal.record(input, false)?;
+                continue;
+            }
+
+            if let Some(template) = options.template.as_ref() {
+                match outcome {
+                    Ok(result) => {
+                        let rendered = template.render(&result)?;
+                        write!(output, "{}", rendered)?;
+                        if !rendered.ends_with('\n') {
+                            writeln!(output)?;
+                        }
+                    }
+                    Err(error) => eprintln!("Failed to get results for '{}': {}", input, error),
+                }
+            } else if format.is_tabular() {
+                let prefix = [(index + 1).to_string(), input.to_string()];
+                let (rows, error) = match outcome {
+                    Ok(result) => match options.fields.as_ref() {
+                        Some(fields) => (
+                            vec![fields.cells(&serde_json::to_value(&result)?, ";")],
+                            String::new(),
+                        ),
+                        None => (table_rows(&result), String::new()),
+                    },
+                    Err(error) => {
+                        let width = options
+                            .fields
+                            .as_ref()
+                            .map_or(TABLE_COLUMNS.len(), |fields| fields.paths.len());
+                        (vec![vec![String::new(); width]], error.to_string())
+                    }
+                };
+                for row in rows {
+                    let mut cells = prefix.to_vec();
+                    cells.extend(row);
+                    cells.push(error.clone());
+                    writeln!(output, "{}", format.format_row(&cells))?;
+                }
+            } else {
+                let mut entry = serde_json::json!({
+                    "line": index + 1,
+                    "input": input,
+                });
+                match outcome {
+                    Ok(result) => {
+                        let result = serde_json::to_value(&result)?;
+                        entry["result"] = match options.fields.as_ref() {
+                            Some(fields) => fields.project(&result),
+                            None => result,
+                        };
+                    }
+                    Err(error) => entry["error"] = crate::utils::error_to_json(&error),
+                }
+                match format {
+                    // Every entry is a document of its own, one per line.
+                    OutputFormat::Text | OutputFormat::Json | OutputFormat::Ndjson => {
+                        writeln!(output, "{}", entry)?
+                    }
+                    _ => writeln!(output, "---\n{}", format.render_value(&entry)?)?,
+                }
+            }
+            output.flush()?;
+
+            // Only journal the input once its output is written, so that a
+            // crash in between makes it be processed again.
+            if let Some(journal) = journal.as_mut() {
+                journal.record(input, succeeded)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The lines of the file at `input`, or of stdin if it is `-`.
+pub fn read_lines(input: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut contents = String::new();
+    if input == "-" {
+        std::io::stdin().read_to_string(&mut contents)?;
+    } else {
+        contents = std::fs::read_to_string(input)
+            .map_err(|err| format!("Failed to read '{input}': {err}"))?;
+    }
+    Ok(contents.lines().map(String::from).collect())
+}
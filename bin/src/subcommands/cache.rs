@@ -0,0 +1,332 @@
+use std::{
+    io::Write,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use odesli_rs::{CachedResult, Catalog};
+
+use super::{
+    batch::{read_lines, DEFAULT_RATE_LIMIT},
+    OdesliSubcommand,
+};
+use crate::{
+    batch::{BatchInput, RateLimiter},
+    errors::ExitError,
+    options::GlobalOptions,
+    utils::format_age,
+};
+
+pub struct CacheSubcommand;
+
+fn input_arg(help: &'static str) -> Arg {
+    Arg::new("input")
+        .help(help)
+        .action(ArgAction::Set)
+        .default_value("-")
+        .value_parser(clap::builder::NonEmptyStringValueParser::new())
+        .num_args(1)
+}
+
+#[async_trait::async_trait]
+impl OdesliSubcommand for CacheSubcommand {
+    fn name(&self) -> &'static str {
+        "cache"
+    }
+
+    fn get_subcommand(&self) -> Command {
+        Command::new(self.name())
+            .about("manage the results stored in the catalog, served by --prefer-catalog")
+            .subcommand_required(true)
+            .subcommand(
+                Command::new("stats")
+                    .about("print the number of results, their age, and the share of hits"),
+            )
+            .subcommand(Command::new("ls").about("list the stored results, newest first"))
+            .subcommand(
+                Command::new("purge").about("delete the results older than a duration").arg(
+                    Arg::new("older-than")
+                        .long("older-than")
+                        .help("Delete the results recorded longer ago than this, e.g. 30d")
+                        .action(ArgAction::Set)
+                        .required(true)
+                        .value_parser(crate::utils::parse_duration)
+                        .num_args(1),
+                ),
+            )
+            .subcommand(
+                Command::new("export").about("write the stored results as JSON lines").arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .help("Write to this file instead of stdout")
+                        .action(ArgAction::Set)
+                        .value_parser(value_parser!(PathBuf))
+                        .num_args(1),
+                ),
+            )
+            .subcommand(
+                Command::new("import")
+                    .about("store the results of an export, keeping the newer ones already stored")
+                    .arg(input_arg("File written by cache export, '-' for stdin")),
+            )
+            .subcommand(
+                Command::new("warm")
+                    .about("look up a list of links or IDs ahead of time, storing their results")
+                    .arg(input_arg("File with one URL or platform:type:id per line, '-' for stdin"))
+                    .arg(
+                        Arg::new("concurrency")
+                            .short('c')
+                            .long("concurrency")
+                            .help("The maximum number of requests in flight")
+                            .action(ArgAction::Set)
+                            .default_value("4")
+                            .value_parser(value_parser!(usize))
+                            .num_args(1),
+                    )
+                    .arg(
+                        Arg::new("rate-limit")
+                            .short('r')
+                            .long("rate-limit")
+                            .help(
+                                "The maximum number of requests per minute, 0 for no limit \
+                                 [default: 10 without an API key, no limit with one]",
+                            )
+                            .action(ArgAction::Set)
+                            .value_parser(value_parser!(u32))
+                            .num_args(1),
+                    ),
+            )
+    }
+
+    async fn handle_subcommand(
+        &self,
+        _final_cmd: &mut Command,
+        cmd_matches: &ArgMatches,
+        options: &GlobalOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        options.format.reject_tabular(self.name())?;
+        let catalog = options.require_catalog()?;
+
+        match cmd_matches.subcommand() {
+            Some(("stats", _)) => print_stats(&catalog, options)?,
+            Some(("ls", _)) => {
+                let results = catalog.results()?;
+                if options.format.is_structured() {
+                    let results = results
+                        .iter()
+                        .map(|cached| {
+                            serde_json::json!({
+                                "entityUniqueId": cached.result.entity_unique_id,
+                                "userCountry": cached.result.user_country,
+                                "recordedAt": unix_timestamp(cached.recorded_at),
+                                "summary": crate::history::summarize(&cached.result),
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    println!("{}", options.format.render_value(&results)?);
+                } else if results.is_empty() {
+                    println!("No results stored");
+                } else {
+                    for cached in results.iter() {
+                        println!(
+                            "{:>4} ago  {} ({})  {}",
+                            format_age(age(cached.recorded_at)),
+                            cached.result.entity_unique_id,
+                            cached.result.user_country,
+                            crate::history::summarize(&cached.result)
+                        );
+                    }
+                }
+            }
+            Some(("purge", matches)) => {
+                let older_than = matches
+                    .get_one::<Duration>("older-than")
+                    .expect("Argument 'older-than' is required");
+                let purged = catalog.purge(*older_than)?;
+                if options.format.is_structured() {
+                    println!(
+                        "{}",
+                        options.format.render_value(&serde_json::json!({ "purged": purged }))?
+                    );
+                } else {
+                    println!("Purged {} result(s)", purged);
+                }
+            }
+            Some(("export", matches)) => {
+                let mut output: Box<dyn Write> = match matches.get_one::<PathBuf>("output") {
+                    Some(path) => Box::new(std::fs::File::create(path)?),
+                    None => Box::new(std::io::stdout()),
+                };
+                for cached in catalog.results()?.iter() {
+                    let entry = serde_json::json!({
+                        "recordedAt": unix_timestamp(cached.recorded_at),
+                        "result": cached.result,
+                    });
+                    writeln!(output, "{}", entry)?;
+                }
+                output.flush()?;
+            }
+            Some(("import", matches)) => {
+                let input =
+                    matches.get_one::<String>("input").expect("Argument 'input' has a default");
+                let mut imported = 0;
+                for (index, line) in read_lines(input)?.iter().enumerate() {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let cached = parse_export_line(line).map_err(|err| {
+                        ExitError::new(
+                            "badInput",
+                            format!("Line {} of '{}': {}", index + 1, input, err),
+                        )
+                    })?;
+                    catalog.import(&cached)?;
+                    imported += 1;
+                }
+                if options.format.is_structured() {
+                    println!(
+                        "{}",
+                        options
+                            .format
+                            .render_value(&serde_json::json!({ "imported": imported }))?
+                    );
+                } else {
+                    println!("Imported {} result(s)", imported);
+                }
+            }
+            Some(("warm", matches)) => warm(matches, options).await?,
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+}
+
+fn print_stats(
+    catalog: &Catalog,
+    options: &GlobalOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stats = catalog.stats()?;
+    if options.format.is_structured() {
+        let stats = serde_json::json!({
+            "path": options.catalog_path,
+            "results": stats.results,
+            "size": stats.size,
+            "oldest": stats.oldest.map(unix_timestamp),
+            "newest": stats.newest.map(unix_timestamp),
+            "hits": stats.hits,
+            "misses": stats.misses,
+            "hitRatio": stats.hit_ratio(),
+        });
+        println!("{}", options.format.render_value(&stats)?);
+        return Ok(());
+    }
+
+    if let Some(path) = options.catalog_path.as_ref() {
+        println!("Catalog: {}", path.display());
+    }
+    println!("Results: {}", stats.results);
+    println!("Size: {}", format_size(stats.size));
+    let or_na = |time: Option<SystemTime>| match time {
+        Some(time) => format!("{} ago", format_age(age(time))),
+        None => "<NA>".to_string(),
+    };
+    println!("Oldest: {}", or_na(stats.oldest));
+    println!("Newest: {}", or_na(stats.newest));
+    match stats.hit_ratio() {
+        Some(ratio) => println!(
+            "Hit ratio: {:.1}% ({} hit(s), {} miss(es))",
+            ratio * 100.0,
+            stats.hits,
+            stats.misses
+        ),
+        None => println!("Hit ratio: <NA> (no lookups yet)"),
+    }
+    Ok(())
+}
+
+/// Look up all the inputs, recording their results in the catalog.
+async fn warm(
+    matches: &ArgMatches,
+    options: &GlobalOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let input = matches.get_one::<String>("input").expect("Argument 'input' has a default");
+    let concurrency =
+        *matches.get_one::<usize>("concurrency").expect("Argument 'concurrency' has a default");
+    let rate_limit = match matches.get_one::<u32>("rate-limit") {
+        Some(rate_limit) => *rate_limit,
+        None if !options.has_api_key() => DEFAULT_RATE_LIMIT,
+        None => 0,
+    };
+
+    let lines = read_lines(input)?;
+    let inputs = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, line)| Some((index, BatchInput::parse(line)?)))
+        .collect::<Vec<_>>();
+    let total = inputs.len();
+
+    // Warming the catalog with the results it already has would be pointless.
+    let client = crate::utils::build_fresh_odesli_client(options)?;
+    if options.dry_run {
+        for (_, input) in inputs.iter() {
+            crate::utils::print_dry_run(input.request_url(&client)?);
+        }
+        return Ok(());
+    }
+
+    let rate_limiter = (rate_limit > 0).then(|| RateLimiter::per_minute(rate_limit));
+    let mut outcomes = crate::batch::resolve_all(client, inputs, concurrency, rate_limiter);
+    let mut failed = 0;
+    while let Some((index, outcome)) = outcomes.recv().await {
+        let input = lines[index].trim();
+        match outcome {
+            Ok(result) => crate::history::record_bulk(options, "cache warm", input, &result).await,
+            Err(error) => {
+                eprintln!("Failed to get results for '{}': {}", input, error);
+                failed += 1;
+            }
+        }
+    }
+
+    if options.format.is_structured() {
+        let summary = serde_json::json!({ "warmed": total - failed, "failed": failed });
+        println!("{}", options.format.render_value(&summary)?);
+    } else {
+        println!("Warmed {} of {} input(s), {} failed", total - failed, total, failed);
+    }
+    Ok(())
+}
+
+/// Parse a line written by `cache export`.
+fn parse_export_line(line: &str) -> Result<CachedResult, Box<dyn std::error::Error>> {
+    let mut entry = serde_json::from_str::<serde_json::Value>(line)?;
+    let recorded_at = entry["recordedAt"].as_u64().ok_or("missing recordedAt")?;
+    let recorded_at = UNIX_EPOCH
+        .checked_add(Duration::from_secs(recorded_at))
+        .ok_or_else(|| format!("recordedAt {} is out of range", recorded_at))?;
+    Ok(CachedResult { recorded_at, result: serde_json::from_value(entry["result"].take())? })
+}
+
+fn unix_timestamp(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or_default()
+}
+
+fn age(time: SystemTime) -> Duration {
+    SystemTime::now().duration_since(time).unwrap_or_default()
+}
+
+/// Format `size` bytes with a binary unit, e.g. `1.5 MiB`.
+fn format_size(size: u64) -> String {
+    let mut size = size as f64;
+    for unit in ["B", "KiB", "MiB"] {
+        if size < 1024.0 {
+            return format!("{size:.1} {unit}");
+        }
+        size /= 1024.0;
+    }
+    format!("{size:.1} GiB")
+}
@@ -0,0 +1,116 @@
+use std::{str::FromStr, time::UNIX_EPOCH};
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use odesli_rs::{EntityUniqueId, Platform};
+
+use super::OdesliSubcommand;
+use crate::options::GlobalOptions;
+
+pub struct CatalogSubcommand;
+
+#[async_trait::async_trait]
+impl OdesliSubcommand for CatalogSubcommand {
+    fn name(&self) -> &'static str {
+        "catalog"
+    }
+
+    fn get_subcommand(&self) -> Command {
+        Command::new(self.name())
+            .about("query the local catalog of looked up IDs, without calling the API")
+            .subcommand_required(true)
+            .subcommand(
+                Command::new("lookup")
+                    .about("find the IDs known to refer to the same entity")
+                    .arg(
+                        Arg::new("unique-id")
+                            .help("The entity unique ID, e.g. SPOTIFY_SONG::7CNUefGBVLn4cLoYv3ej8x")
+                            .action(ArgAction::Set)
+                            .required(true)
+                            .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                            .num_args(1),
+                    )
+                    .arg(
+                        Arg::new("platform")
+                            .short('p')
+                            .long("platform")
+                            .help("Only print the ID on this platform")
+                            .action(ArgAction::Set)
+                            .required(false)
+                            .value_parser(clap::builder::EnumValueParser::<Platform>::new())
+                            .num_args(1),
+                    ),
+            )
+    }
+
+    async fn handle_subcommand(
+        &self,
+        _final_cmd: &mut Command,
+        cmd_matches: &ArgMatches,
+        options: &GlobalOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        options.format.reject_tabular(self.name())?;
+        let catalog = options.require_catalog()?;
+
+        match cmd_matches.subcommand() {
+            Some(("lookup", lookup_matches)) => {
+                let unique_id = lookup_matches
+                    .get_one::<String>("unique-id")
+                    .expect("Argument 'unique-id' is required");
+                let unique_id = EntityUniqueId::from_str(unique_id)?;
+
+                if let Some(platform) = lookup_matches.get_one::<Platform>("platform") {
+                    match catalog.find_equivalent(&unique_id, platform)? {
+                        Some(equivalent) => {
+                            if options.format.is_structured() {
+                                println!(
+                                    "{}",
+                                    options.format.render_value(&equivalent.to_string())?
+                                );
+                            } else {
+                                println!("{}", equivalent);
+                            }
+                        }
+                        None => {
+                            return Err(format!(
+                                "No {} ID known for {}",
+                                platform.as_str(),
+                                unique_id
+                            )
+                            .into())
+                        }
+                    }
+                    return Ok(());
+                }
+
+                let equivalents = catalog.equivalents(&unique_id)?;
+                if options.format.is_structured() {
+                    let equivalents = equivalents
+                        .iter()
+                        .map(|equivalent| {
+                            serde_json::json!({
+                                "entityUniqueId": equivalent.unique_id.to_string(),
+                                "userCountry": equivalent.user_country,
+                                "recordedAt": equivalent
+                                    .recorded_at
+                                    .duration_since(UNIX_EPOCH)
+                                    .map(|duration| duration.as_secs())
+                                    .unwrap_or_default(),
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    println!("{}", options.format.render_value(&equivalents)?);
+                } else if equivalents.is_empty() {
+                    println!("No IDs known for {}", unique_id);
+                } else {
+                    println!("IDs known for {}:", unique_id);
+                    for equivalent in equivalents.iter() {
+                        println!("  {} ({})", equivalent.unique_id, equivalent.user_country);
+                    }
+                }
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use odesli_rs::Verdict;
+
+use super::OdesliSubcommand;
+use crate::{errors::ExitError, options::GlobalOptions};
+
+pub struct CompareSubcommand;
+
+fn input_arg(name: &'static str) -> Arg {
+    Arg::new(name)
+        .help("A URL, a platform URI or an entity unique ID, as accepted by get")
+        .action(ArgAction::Set)
+        .required(true)
+        .value_parser(clap::builder::NonEmptyStringValueParser::new())
+        .num_args(1)
+}
+
+#[async_trait::async_trait]
+impl OdesliSubcommand for CompareSubcommand {
+    fn name(&self) -> &'static str {
+        "compare"
+    }
+
+    fn get_subcommand(&self) -> Command {
+        Command::new(self.name())
+            .about("check whether two links are for the same release, exiting with 0 if they are")
+            .arg(input_arg("a"))
+            .arg(input_arg("b"))
+    }
+
+    async fn handle_subcommand(
+        &self,
+        _final_cmd: &mut Command,
+        cmd_matches: &ArgMatches,
+        options: &GlobalOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        options.format.reject_tabular(self.name())?;
+        let a = cmd_matches.get_one::<String>("a").expect("Argument 'a' is required");
+        let b = cmd_matches.get_one::<String>("b").expect("Argument 'b' is required");
+        let a = odesli_rs::parse_input(a)?;
+        let b = odesli_rs::parse_input(b)?;
+
+        let client = crate::utils::build_odesli_client(options)?;
+        if options.dry_run {
+            crate::utils::print_dry_run(client.request_url_by_input(&a)?);
+            crate::utils::print_dry_run(client.request_url_by_input(&b)?);
+            return Ok(());
+        }
+
+        let (a_result, b_result) =
+            tokio::try_join!(client.get_by_input(&a), client.get_by_input(&b))?;
+        crate::history::record_bulk(options, self.name(), &a.input, &a_result).await;
+        crate::history::record_bulk(options, self.name(), &b.input, &b_result).await;
+        let comparison = odesli_rs::compare(&a_result, &b_result);
+
+        if options.format.is_structured() {
+            let comparison = serde_json::json!({
+                "a": a_result.entity_unique_id,
+                "b": b_result.entity_unique_id,
+                "verdict": comparison.verdict,
+                "reasons": comparison.reasons,
+            });
+            println!("{}", options.format.render_value(&comparison)?);
+        } else {
+            println!("A: {} ({})", crate::history::summarize(&a_result), a_result.entity_unique_id);
+            println!("B: {} ({})", crate::history::summarize(&b_result), b_result.entity_unique_id);
+            println!();
+            match comparison.verdict {
+                Verdict::Same => println!("Same release:"),
+                Verdict::Different => println!("Different releases:"),
+                Verdict::Uncertain => println!("Uncertain:"),
+            }
+            for reason in comparison.reasons.iter() {
+                println!("  - {}", reason);
+            }
+        }
+
+        match comparison.verdict {
+            Verdict::Same => Ok(()),
+            Verdict::Different => {
+                Err(ExitError::new("different", "The links are for different releases").into())
+            }
+            Verdict::Uncertain => Err(ExitError::new(
+                "uncertain",
+                "Could not tell whether the links are for the same release",
+            )
+            .into()),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use clap::{ArgMatches, Command};
+
+use super::OdesliSubcommand;
+use crate::options::GlobalOptions;
+
+pub struct ConfigSubcommand;
+
+#[async_trait::async_trait]
+impl OdesliSubcommand for ConfigSubcommand {
+    fn name(&self) -> &'static str {
+        "config"
+    }
+
+    fn get_subcommand(&self) -> Command {
+        Command::new(self.name())
+            .about("inspect the configuration")
+            .subcommand_required(true)
+            .subcommand(Command::new("show").about(
+                "print the configuration in effect, from the config file and the global flags",
+            ))
+    }
+
+    async fn handle_subcommand(
+        &self,
+        _final_cmd: &mut Command,
+        cmd_matches: &ArgMatches,
+        options: &GlobalOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        options.format.reject_tabular(self.name())?;
+        match cmd_matches.subcommand() {
+            Some(("show", _)) => {
+                let settings = options.effective_settings();
+                if options.format.is_structured() {
+                    println!("{}", options.format.render_value(&settings)?);
+                    return Ok(());
+                }
+
+                match options.config_path.as_ref() {
+                    Some(path) => println!("# Configuration file: {}", path.display()),
+                    None => println!("# No configuration file"),
+                }
+                if let Some(profile) = options.profile.as_ref() {
+                    println!("# Profile: {}", profile);
+                }
+                print!("{}", toml::to_string(&settings)?);
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+use clap::{Arg, ArgAction, ArgMatches, Command, ValueEnum};
+use odesli_rs::Platform;
+
+use super::OdesliSubcommand;
+use crate::{errors::ExitError, options::GlobalOptions};
+
+/// Which link of a platform to print.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum NativeApp {
+    /// The URI opening the mobile app.
+    Mobile,
+    /// The URI opening the desktop app.
+    Desktop,
+}
+
+pub struct ConvertSubcommand;
+
+#[async_trait::async_trait]
+impl OdesliSubcommand for ConvertSubcommand {
+    fn name(&self) -> &'static str {
+        "convert"
+    }
+
+    fn get_subcommand(&self) -> Command {
+        Command::new(self.name())
+            .about("print the link of a URL on other platforms, one per line")
+            .after_help(
+                "Exits with code 3 if a target platform has no match, after printing the others.",
+            )
+            .arg(
+                Arg::new("url")
+                    .help("The URL to convert")
+                    .action(ArgAction::Set)
+                    .required(true)
+                    .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("to")
+                    .long("to")
+                    .help("The platform to print the link of, can be repeated or comma separated")
+                    .action(ArgAction::Append)
+                    .required(true)
+                    .value_delimiter(',')
+                    .value_parser(clap::builder::EnumValueParser::<Platform>::new())
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("native")
+                    .long("native")
+                    .help("Print the URI opening the platform's app instead of the URL")
+                    .action(ArgAction::Set)
+                    .required(false)
+                    .value_parser(clap::builder::EnumValueParser::<NativeApp>::new())
+                    .num_args(1),
+            )
+    }
+
+    async fn handle_subcommand(
+        &self,
+        _final_cmd: &mut Command,
+        cmd_matches: &ArgMatches,
+        options: &GlobalOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        options.format.reject_tabular(self.name())?;
+        let url = cmd_matches.get_one::<String>("url").expect("Argument 'url' is required");
+        let targets = cmd_matches
+            .get_many::<Platform>("to")
+            .expect("Argument 'to' is required")
+            .collect::<Vec<_>>();
+        let native = cmd_matches.get_one::<NativeApp>("native").copied();
+
+        let client = crate::utils::build_odesli_client(options)?;
+        if options.dry_run {
+            crate::utils::print_dry_run(client.request_url_by_url(url)?);
+            return Ok(());
+        }
+
+        let result = client.get_by_url(url).await?;
+        crate::history::record(options, self.name(), url, &result).await;
+
+        let links = targets
+            .iter()
+            .map(|platform| {
+                let link = result.get_platform_url(platform).and_then(|link| match native {
+                    None => Some(link.url.clone()),
+                    Some(NativeApp::Mobile) => link.native_app_uri_mobile.clone(),
+                    Some(NativeApp::Desktop) => link.native_app_uri_desktop.clone(),
+                });
+                (*platform, link)
+            })
+            .collect::<Vec<_>>();
+
+        if options.format.is_structured() {
+            let links = links
+                .iter()
+                .map(|(platform, link)| (platform.as_str().to_string(), serde_json::json!(link)))
+                .collect::<serde_json::Map<_, _>>();
+            println!("{}", options.format.render_value(&links)?);
+        } else {
+            for link in links.iter().filter_map(|(_, link)| link.as_ref()) {
+                println!("{}", link);
+            }
+        }
+
+        let missing = links
+            .iter()
+            .filter(|(_, link)| link.is_none())
+            .map(|(platform, _)| platform.as_str())
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            let what = match native {
+                None => "No match",
+                Some(NativeApp::Mobile) => "No mobile app URI",
+                Some(NativeApp::Desktop) => "No desktop app URI",
+            };
+            return Err(
+                ExitError::new("noMatch", format!("{} on {}", what, missing.join(", "))).into()
+            );
+        }
+
+        Ok(())
+    }
+}
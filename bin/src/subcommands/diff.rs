@@ -0,0 +1,101 @@
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use odesli_rs::{Change, LinksAPIResult};
+
+use super::OdesliSubcommand;
+use crate::options::GlobalOptions;
+
+pub struct DiffSubcommand;
+
+#[async_trait::async_trait]
+impl OdesliSubcommand for DiffSubcommand {
+    fn name(&self) -> &'static str {
+        "diff"
+    }
+
+    fn get_subcommand(&self) -> Command {
+        Command::new(self.name())
+            .about("show what changed between two saved JSON results")
+            .arg(
+                Arg::new("old")
+                    .help("Path to the older result, as dumped by --json")
+                    .action(ArgAction::Set)
+                    .required(true)
+                    .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("new")
+                    .help("Path to the newer result, as dumped by --json")
+                    .action(ArgAction::Set)
+                    .required(true)
+                    .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                    .num_args(1),
+            )
+    }
+
+    async fn handle_subcommand(
+        &self,
+        _final_cmd: &mut Command,
+        cmd_matches: &ArgMatches,
+        options: &GlobalOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        options.format.reject_tabular(self.name())?;
+        let old_path = cmd_matches.get_one::<String>("old").expect("Argument 'old' is required");
+        let new_path = cmd_matches.get_one::<String>("new").expect("Argument 'new' is required");
+
+        let old = read_result(old_path)?;
+        let new = read_result(new_path)?;
+        let diff = odesli_rs::diff(&old, &new);
+
+        if options.format.is_structured() {
+            println!("{}", options.format.render_value(&diff)?);
+        } else if diff.is_empty() {
+            println!("No changes");
+        } else {
+            for change in diff.changes.iter() {
+                println!("{}", format_change(change));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn read_result(path: &str) -> Result<LinksAPIResult, Box<dyn std::error::Error>> {
+    let contents =
+        std::fs::read_to_string(path).map_err(|err| format!("Failed to read '{path}': {err}"))?;
+    let result = serde_json::from_str(&contents)
+        .map_err(|err| format!("Failed to parse '{path}': {err}"))?;
+    Ok(result)
+}
+
+pub fn format_change(change: &Change) -> String {
+    fn or_na(value: &Option<String>) -> &str {
+        value.as_deref().unwrap_or("<NA>")
+    }
+
+    match change {
+        Change::PageUrlChanged { old, new } => format!("~ page: {old} -> {new}"),
+        Change::PlatformAdded { platform, link } => {
+            format!("+ {}: {}", platform.as_str(), link.url)
+        }
+        Change::PlatformRemoved { platform, link } => {
+            format!("- {}: {}", platform.as_str(), link.url)
+        }
+        Change::UrlChanged { platform, old, new } => {
+            format!("~ {}: url {old} -> {new}", platform.as_str())
+        }
+        Change::EntityChanged { platform, old_entity_unique_id, new_entity_unique_id } => {
+            format!(
+                "~ {}: entity {old_entity_unique_id} -> {new_entity_unique_id}",
+                platform.as_str()
+            )
+        }
+        Change::TitleChanged { platform, old, new } => {
+            format!("~ {}: title {:?} -> {:?}", platform.as_str(), or_na(old), or_na(new))
+        }
+        Change::ArtistChanged { platform, old, new } => {
+            format!("~ {}: artist {:?} -> {:?}", platform.as_str(), or_na(old), or_na(new))
+        }
+    }
+}
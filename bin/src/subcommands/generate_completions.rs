@@ -2,6 +2,7 @@ use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
 use clap_complete::{generate, Shell};
 
 use super::OdesliSubcommand;
+use crate::options::GlobalOptions;
 
 pub struct GenerateCompletionsSubcommand;
 
@@ -26,8 +27,7 @@ impl OdesliSubcommand for GenerateCompletionsSubcommand {
         &self,
         final_cmd: &mut Command,
         cmd_matches: &ArgMatches,
-        _api_key: Option<String>,
-        _dump_json: bool,
+        _options: &GlobalOptions,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let shell =
             cmd_matches.get_one::<Shell>("shell").copied().expect("Argument 'shell' is required");
@@ -0,0 +1,65 @@
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+use super::OdesliSubcommand;
+use crate::options::GlobalOptions;
+
+pub struct GetSubcommand;
+
+#[async_trait::async_trait]
+impl OdesliSubcommand for GetSubcommand {
+    fn name(&self) -> &'static str {
+        "get"
+    }
+
+    fn get_subcommand(&self) -> Command {
+        Command::new(self.name())
+            .about("find matches using a URL, a platform URI or an entity unique ID")
+            .arg(
+                Arg::new("input")
+                    .help(
+                        "What to search, e.g. https://open.spotify.com/track/<id>, \
+                         spotify:track:<id>, SPOTIFY_SONG::<id> or https://song.link/s/<id>",
+                    )
+                    .action(ArgAction::Set)
+                    .required(true)
+                    .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                    .num_args(1),
+            )
+    }
+
+    async fn handle_subcommand(
+        &self,
+        _final_cmd: &mut Command,
+        cmd_matches: &ArgMatches,
+        options: &GlobalOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let input = cmd_matches.get_one::<String>("input").expect("Argument 'input' is required");
+        let input = odesli_rs::parse_input(input)?;
+
+        let client = crate::utils::build_odesli_client(options)?;
+        if options.dry_run {
+            crate::utils::print_dry_run(client.request_url_by_input(&input)?);
+            return Ok(());
+        }
+
+        let result = client.get_by_input(&input).await?;
+        crate::history::record(options, self.name(), &input.input, &result).await;
+        crate::output::print_result(options, &result, || {
+            println!("Input Details:");
+            println!("  Input: {}", input.input);
+            println!("  Kind: {}", super::parse::describe_kind(input.kind));
+            if let Some(platform) = input.platform.as_ref() {
+                println!("  Platform: {}", platform.display_name());
+            }
+            if let Some(entity_type) = input.entity_type.as_ref() {
+                println!("  Type: {}", entity_type.as_str());
+            }
+            if let Some(id) = input.id.as_ref() {
+                println!("  ID: {}", id);
+            }
+            println!();
+        })?;
+
+        Ok(())
+    }
+}
@@ -1,7 +1,8 @@
 use clap::{Arg, ArgAction, ArgMatches, Command};
-use odesli_rs::{EntityType, OdesliError, Platform};
+use odesli_rs::{EntityType, Platform};
 
 use super::OdesliSubcommand;
+use crate::options::GlobalOptions;
 
 pub struct GetIdSubcommand;
 
@@ -46,10 +47,9 @@ impl OdesliSubcommand for GetIdSubcommand {
         &self,
         _final_cmd: &mut Command,
         cmd_matches: &ArgMatches,
-        api_key: Option<String>,
-        dump_json: bool,
+        options: &GlobalOptions,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let client = crate::utils::build_odesli_client(api_key);
+        let client = crate::utils::build_odesli_client(options)?;
 
         let entity_id = cmd_matches.get_one::<String>("id").expect("Argument 'id' is required");
         let query_platform =
@@ -58,36 +58,23 @@ impl OdesliSubcommand for GetIdSubcommand {
             .get_one::<EntityType>("entity-type")
             .expect("Argument 'entity-type' is required");
 
-        match client.get_by_id(entity_id, query_platform, entity_type).await {
-            Ok(result) => {
-                if dump_json {
-                    let result_json = serde_json::to_string_pretty(&result)
-                        .expect("result is already json parsed");
-                    println!("{}", result_json);
-                } else {
-                    println!("Input Details:");
-                    println!("  ID: {}", entity_id);
-                    println!("  Platform: {:?}", query_platform);
-                    println!("  Type: {:?}", entity_type);
-                    println!();
-                    crate::utils::pretty_print_api_result(&result);
-                }
-            }
-            Err(error) => {
-                if dump_json {
-                    eprintln!("Failed to get results: {}", error);
-                    match error {
-                        OdesliError::ParseError { body, .. }
-                        | OdesliError::Non200StatusCode { body, .. } => {
-                            println!("{}", body);
-                        }
-                        _ => {}
-                    }
-                } else {
-                    println!("Failed to get results: {}", error);
-                }
-            }
-        };
+        if options.dry_run {
+            let url = client.request_url_by_id(entity_id, query_platform, entity_type)?;
+            crate::utils::print_dry_run(url);
+            return Ok(());
+        }
+
+        let result = client.get_by_id(entity_id, query_platform, entity_type).await?;
+        // Recorded as a `platform:type:id` batch input, so that it can be rerun.
+        let input = format!("{}:{}:{}", query_platform.as_str(), entity_type.as_str(), entity_id);
+        crate::history::record(options, self.name(), &input, &result).await;
+        crate::output::print_result(options, &result, || {
+            println!("Input Details:");
+            println!("  ID: {}", entity_id);
+            println!("  Platform: {}", query_platform.display_name());
+            println!("  Type: {:?}", entity_type);
+            println!();
+        })?;
 
         Ok(())
     }
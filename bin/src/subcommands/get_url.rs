@@ -1,7 +1,7 @@
 use clap::{Arg, ArgAction, ArgMatches, Command};
-use odesli_rs::OdesliError;
 
 use super::OdesliSubcommand;
+use crate::options::GlobalOptions;
 
 pub struct GetUrlSubcommand;
 
@@ -26,38 +26,21 @@ impl OdesliSubcommand for GetUrlSubcommand {
         &self,
         _final_cmd: &mut Command,
         cmd_matches: &ArgMatches,
-        api_key: Option<String>,
-        dump_json: bool,
+        options: &GlobalOptions,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let client = crate::utils::build_odesli_client(api_key);
+        let client = crate::utils::build_odesli_client(options)?;
         let url = cmd_matches.get_one::<String>("url").expect("Argument 'url' is required");
 
-        match client.get_by_url(url).await {
-            Ok(result) => {
-                if dump_json {
-                    let result_json = serde_json::to_string_pretty(&result)
-                        .expect("result is already json parsed");
-                    println!("{}", result_json);
-                } else {
-                    println!("Input URL: {url}");
-                    crate::utils::pretty_print_api_result(&result);
-                }
-            }
-            Err(error) => {
-                if dump_json {
-                    eprintln!("Failed to get results: {}", error);
-                    match error {
-                        OdesliError::ParseError { body, .. }
-                        | OdesliError::Non200StatusCode { body, .. } => {
-                            println!("{}", body);
-                        }
-                        _ => {}
-                    }
-                } else {
-                    println!("Failed to get results: {}", error);
-                }
-            }
-        };
+        if options.dry_run {
+            crate::utils::print_dry_run(client.request_url_by_url(url)?);
+            return Ok(());
+        }
+
+        let result = client.get_by_url(url).await?;
+        crate::history::record(options, self.name(), url, &result).await;
+        crate::output::print_result(options, &result, || {
+            println!("Input URL: {url}");
+        })?;
 
         Ok(())
     }
@@ -0,0 +1,186 @@
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use odesli_rs::ParsedInput;
+
+use super::OdesliSubcommand;
+use crate::{
+    batch::BatchInput, errors::ExitError, history::HistoryEntry, options::GlobalOptions,
+    utils::format_age,
+};
+
+pub struct HistorySubcommand;
+
+fn limit_arg() -> Arg {
+    Arg::new("limit")
+        .short('n')
+        .long("limit")
+        .help("The maximum number of lookups to print, newest first")
+        .action(ArgAction::Set)
+        .value_parser(value_parser!(usize))
+        .default_value("20")
+        .num_args(1)
+}
+
+fn id_arg() -> Arg {
+    Arg::new("id")
+        .help("The number of the lookup, as printed by history list")
+        .action(ArgAction::Set)
+        .required(true)
+        .value_parser(value_parser!(u64))
+        .num_args(1)
+}
+
+#[async_trait::async_trait]
+impl OdesliSubcommand for HistorySubcommand {
+    fn name(&self) -> &'static str {
+        "history"
+    }
+
+    fn get_subcommand(&self) -> Command {
+        Command::new(self.name())
+            .about("browse and replay the lookups made before")
+            .subcommand_required(true)
+            .subcommand(Command::new("list").about("list the latest lookups").arg(limit_arg()))
+            .subcommand(
+                Command::new("search")
+                    .about("find lookups by the words of their input, title, artist or links")
+                    .arg(
+                        Arg::new("query")
+                            .help("The words to look for, ignoring case")
+                            .action(ArgAction::Append)
+                            .required(true)
+                            .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                            .num_args(1..),
+                    )
+                    .arg(limit_arg()),
+            )
+            .subcommand(
+                Command::new("show").about("print the stored result of a lookup").arg(id_arg()),
+            )
+            .subcommand(
+                Command::new("rerun")
+                    .about("make a lookup again, for the same country unless --country is given")
+                    .arg(id_arg()),
+            )
+    }
+
+    async fn handle_subcommand(
+        &self,
+        _final_cmd: &mut Command,
+        cmd_matches: &ArgMatches,
+        options: &GlobalOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let history = options.history().ok_or("The history is disabled")?;
+
+        match cmd_matches.subcommand() {
+            Some((name @ ("list" | "search"), matches)) => {
+                options.format.reject_tabular(&format!("{} {}", self.name(), name))?;
+                let limit =
+                    *matches.get_one::<usize>("limit").expect("Argument 'limit' has a default");
+                let query = (name == "search").then(|| {
+                    let words =
+                        matches.get_many::<String>("query").expect("Argument 'query' is required");
+                    words.cloned().collect::<Vec<_>>().join(" ")
+                });
+
+                let entries = history
+                    .entries()?
+                    .into_iter()
+                    .rev()
+                    .filter(|entry| query.as_ref().is_none_or(|query| entry.matches(query)))
+                    .take(limit)
+                    .collect::<Vec<_>>();
+
+                if options.format.is_structured() {
+                    let entries = entries.iter().map(HistoryEntry::overview).collect::<Vec<_>>();
+                    println!("{}", options.format.render_value(&entries)?);
+                } else if entries.is_empty() {
+                    match name {
+                        "search" => println!("No lookups match"),
+                        _ => println!("No lookups recorded"),
+                    }
+                } else {
+                    for entry in entries.iter() {
+                        println!(
+                            "#{:<5} {:>4} ago  {}",
+                            entry.id,
+                            format_age(entry.age()),
+                            entry.summary
+                        );
+                        println!("       {} {} ({})", entry.command, entry.input, entry.country);
+                    }
+                }
+            }
+            Some(("show", matches)) => {
+                let entry = find_entry(&history, matches)?;
+                crate::output::print_result(options, &entry.result, || {
+                    println!("Lookup #{}:", entry.id);
+                    println!("  Made: {} ago", format_age(entry.age()));
+                    println!("  Command: {}", entry.command);
+                    println!("  Input: {}", entry.input);
+                    println!("  Country: {}", entry.country);
+                    println!();
+                })?;
+            }
+            Some(("rerun", matches)) => {
+                let entry = find_entry(&history, matches)?;
+                let mut builder = crate::utils::odesli_client_builder(options)?;
+                if !options.country_from_flag {
+                    builder = builder.with_user_country(entry.country.clone());
+                }
+                let client = builder.build();
+
+                // `get`, `compare` and `watch` are recorded with any input
+                // they accept, the other commands with URLs or
+                // `platform:type:id` triples.
+                let input = match entry.command.as_str() {
+                    "get" | "compare" | "watch" => {
+                        RerunInput::Parsed(odesli_rs::parse_input(&entry.input)?)
+                    }
+                    _ => RerunInput::Batch(BatchInput::parse(&entry.input).ok_or_else(|| {
+                        format!("Cannot rerun lookup #{}, its input is empty", entry.id)
+                    })?),
+                };
+
+                if options.dry_run {
+                    let url = match &input {
+                        RerunInput::Parsed(input) => client.request_url_by_input(input)?,
+                        RerunInput::Batch(input) => input.request_url(&client)?,
+                    };
+                    crate::utils::print_dry_run(url);
+                    return Ok(());
+                }
+
+                let result = match &input {
+                    RerunInput::Parsed(input) => client.get_by_input(input).await?,
+                    RerunInput::Batch(input) => input.resolve(&client).await?,
+                };
+                crate::history::record(options, &entry.command, &entry.input, &result).await;
+                crate::output::print_result(options, &result, || {
+                    println!("Rerun of lookup #{}:", entry.id);
+                    println!("  Command: {}", entry.command);
+                    println!("  Input: {}", entry.input);
+                    println!();
+                })?;
+            }
+            _ => unreachable!(),
+        }
+
+        Ok(())
+    }
+}
+
+fn find_entry(
+    history: &crate::history::History,
+    matches: &ArgMatches,
+) -> Result<HistoryEntry, Box<dyn std::error::Error>> {
+    let id = *matches.get_one::<u64>("id").expect("Argument 'id' is required");
+    history
+        .get(id)?
+        .ok_or_else(|| ExitError::new("notFound", format!("No lookup #{id} in the history")).into())
+}
+
+/// The input of a lookup to rerun.
+enum RerunInput {
+    Parsed(ParsedInput),
+    Batch(BatchInput),
+}
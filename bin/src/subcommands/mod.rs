@@ -1,10 +1,25 @@
 use clap::{ArgMatches, Command};
 use lazy_static::lazy_static;
 
+use crate::options::GlobalOptions;
+
+pub mod batch;
+pub mod cache;
+pub mod catalog;
+pub mod compare;
+pub mod config;
+pub mod convert;
+pub mod diff;
 pub mod generate_completions;
+pub mod get;
 pub mod get_id;
 pub mod get_url;
+pub mod history;
+pub mod parse;
 pub mod platforms;
+pub mod render;
+pub mod search;
+pub mod watch;
 
 #[async_trait::async_trait]
 pub trait OdesliSubcommand: Sync {
@@ -14,16 +29,28 @@ pub trait OdesliSubcommand: Sync {
         &self,
         final_cmd: &mut Command,
         cmd_matches: &ArgMatches,
-        api_key: Option<String>,
-        dump_json: bool,
+        options: &GlobalOptions,
     ) -> Result<(), Box<dyn std::error::Error>>;
 }
 
 lazy_static! {
-    pub static ref SUBCOMMANDS: [Box<dyn OdesliSubcommand>; 4] = [
+    pub static ref SUBCOMMANDS: [Box<dyn OdesliSubcommand>; 17] = [
+        Box::new(get::GetSubcommand),
         Box::new(get_url::GetUrlSubcommand),
         Box::new(get_id::GetIdSubcommand),
+        Box::new(convert::ConvertSubcommand),
+        Box::new(batch::BatchSubcommand),
         Box::new(platforms::PlatformsSubcommand),
+        Box::new(diff::DiffSubcommand),
+        Box::new(compare::CompareSubcommand),
+        Box::new(parse::ParseSubcommand),
+        Box::new(render::RenderSubcommand),
+        Box::new(watch::WatchSubcommand),
+        Box::new(history::HistorySubcommand),
+        Box::new(catalog::CatalogSubcommand),
+        Box::new(cache::CacheSubcommand),
+        Box::new(search::SearchSubcommand),
+        Box::new(config::ConfigSubcommand),
         Box::new(generate_completions::GenerateCompletionsSubcommand),
     ];
 }
@@ -0,0 +1,143 @@
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use odesli_rs::{InputKind, OdesliError, ParsedInput};
+
+use super::OdesliSubcommand;
+use crate::{errors::ExitError, options::GlobalOptions, subcommands::batch::read_lines};
+
+pub struct ParseSubcommand;
+
+#[async_trait::async_trait]
+impl OdesliSubcommand for ParseSubcommand {
+    fn name(&self) -> &'static str {
+        "parse"
+    }
+
+    fn get_subcommand(&self) -> Command {
+        Command::new(self.name()).about("show what links refer to, without calling the API").arg(
+            Arg::new("input")
+                .help(
+                    "URLs, platform URIs or entity unique IDs, as accepted by get. Without \
+                     any, one per line is read from stdin",
+                )
+                .action(ArgAction::Append)
+                .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                .num_args(0..),
+        )
+    }
+
+    async fn handle_subcommand(
+        &self,
+        _final_cmd: &mut Command,
+        cmd_matches: &ArgMatches,
+        options: &GlobalOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        options.format.reject_tabular(self.name())?;
+        let inputs = match cmd_matches.get_many::<String>("input") {
+            Some(inputs) => inputs.cloned().collect::<Vec<_>>(),
+            // Blank lines and comments are skipped, like batch does.
+            None => read_lines("-")?
+                .into_iter()
+                .filter(|line| !line.trim().is_empty() && !line.trim().starts_with('#'))
+                .collect(),
+        };
+        let outcomes = inputs
+            .iter()
+            .map(|input| (input.trim(), odesli_rs::parse_input(input)))
+            .collect::<Vec<_>>();
+
+        if options.format.is_structured() {
+            let values = outcomes
+                .iter()
+                .map(|(input, outcome)| outcome_to_json(input, outcome))
+                .collect::<Result<Vec<_>, _>>()?;
+            println!("{}", options.format.render_value(&values)?);
+        } else {
+            for (index, (input, outcome)) in outcomes.iter().enumerate() {
+                if index > 0 {
+                    println!();
+                }
+                println!("{}", input);
+                print_outcome(outcome);
+            }
+        }
+
+        let failed = outcomes.iter().filter(|(_, outcome)| outcome.is_err()).count();
+        if failed > 0 {
+            return Err(ExitError::new(
+                "badInput",
+                format!("{} of {} input(s) could not be parsed", failed, outcomes.len()),
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+fn print_outcome(outcome: &Result<ParsedInput, OdesliError>) {
+    let parsed = match outcome {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            println!("  Error: {}", error);
+            return;
+        }
+    };
+
+    println!("  Kind: {}", describe_kind(parsed.kind));
+    match parsed.platform.as_ref() {
+        Some(platform) => println!("  Platform: {}", platform.display_name()),
+        None => println!("  Platform: unknown, the API may still know the URL"),
+    }
+    if let Some(entity_type) = parsed.entity_type.as_ref() {
+        println!("  Entity type: {}", entity_type.as_str());
+    }
+    if let Some(id) = parsed.id.as_ref() {
+        println!("  ID: {}", id);
+    }
+    if let Some(url) = parsed.canonical_url() {
+        println!("  Canonical URL: {}", url);
+    }
+    match parsed.entity_unique_id() {
+        Some(unique_id) => println!("  Entity unique ID: {}", unique_id),
+        None if parsed.platform.is_some() => {
+            println!("  Entity unique ID: unknown, the API is needed to find it")
+        }
+        None => {}
+    }
+}
+
+/// The kind of an input, as printed for people.
+pub fn describe_kind(kind: InputKind) -> &'static str {
+    match kind {
+        InputKind::Url => "URL",
+        InputKind::Uri => "platform URI",
+        InputKind::UniqueId => "entity unique ID",
+        InputKind::SongLinkPage => "song.link page",
+    }
+}
+
+/// Describe `outcome` as a JSON object with the input, and either what was
+/// learned from it or the error.
+fn outcome_to_json(
+    input: &str,
+    outcome: &Result<ParsedInput, OdesliError>,
+) -> Result<serde_json::Value, serde_json::Error> {
+    let parsed = match outcome {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            return Ok(serde_json::json!({
+                "input": input,
+                "error": crate::utils::error_to_json(error),
+            }))
+        }
+    };
+
+    let mut value = serde_json::to_value(parsed)?;
+    if let serde_json::Value::Object(map) = &mut value {
+        map.insert("canonicalUrl".to_string(), serde_json::to_value(parsed.canonical_url())?);
+        map.insert(
+            "entityUniqueId".to_string(),
+            serde_json::to_value(parsed.entity_unique_id().map(|id| id.to_string()))?,
+        );
+    }
+    Ok(value)
+}
@@ -3,6 +3,7 @@ use odesli_rs::Platform;
 use strum::IntoEnumIterator;
 
 use super::OdesliSubcommand;
+use crate::options::GlobalOptions;
 
 pub struct PlatformsSubcommand;
 
@@ -20,13 +21,23 @@ impl OdesliSubcommand for PlatformsSubcommand {
         &self,
         _final_cmd: &mut Command,
         _cmd_matches: &ArgMatches,
-        _api_key: Option<String>,
-        _dump_json: bool,
+        options: &GlobalOptions,
     ) -> Result<(), Box<dyn std::error::Error>> {
+        options.format.reject_tabular(self.name())?;
+        if options.format.is_structured() {
+            let platforms = Platform::iter()
+                .map(|platform| {
+                    serde_json::json!({ "id": platform.as_str(), "name": platform.display_name() })
+                })
+                .collect::<Vec<_>>();
+            println!("{}", options.format.render_value(&platforms)?);
+            return Ok(());
+        }
+
         println!("Here are all the platforms supported by Odesli:\n");
 
         for (num, platform) in Platform::iter().enumerate() {
-            println!("{}. {:?}: {}", num + 1, platform, platform.as_str())
+            println!("{}. {}: {}", num + 1, platform.display_name(), platform.as_str())
         }
 
         Ok(())
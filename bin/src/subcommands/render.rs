@@ -0,0 +1,92 @@
+use std::io::Read;
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use odesli_rs::LinksAPIResult;
+
+use super::OdesliSubcommand;
+use crate::{errors::ExitError, options::GlobalOptions};
+
+pub struct RenderSubcommand;
+
+#[async_trait::async_trait]
+impl OdesliSubcommand for RenderSubcommand {
+    fn name(&self) -> &'static str {
+        "render"
+    }
+
+    fn get_subcommand(&self) -> Command {
+        Command::new(self.name())
+            .about("print a saved JSON result in any format or template, without calling the API")
+            .arg(
+                Arg::new("file")
+                    .help("Path to the result, as dumped by --json, or - for stdin")
+                    .action(ArgAction::Set)
+                    .default_value("-")
+                    .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("validate")
+                    .long("validate")
+                    .help(
+                        "Only check the result against the model, listing unknown fields and \
+                         platforms, and exit with code 2 if there are any",
+                    )
+                    .num_args(0)
+                    .action(ArgAction::SetTrue)
+                    .required(false),
+            )
+    }
+
+    async fn handle_subcommand(
+        &self,
+        _final_cmd: &mut Command,
+        cmd_matches: &ArgMatches,
+        options: &GlobalOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let path = cmd_matches.get_one::<String>("file").expect("Argument 'file' has a default");
+
+        let mut contents = String::new();
+        if path == "-" {
+            std::io::stdin().read_to_string(&mut contents)?;
+        } else {
+            contents = std::fs::read_to_string(path)
+                .map_err(|err| format!("Failed to read '{path}': {err}"))?;
+        }
+        let value = serde_json::from_str::<serde_json::Value>(&contents).map_err(|err| {
+            ExitError::new("badInput", format!("Failed to parse '{path}': {err}"))
+        })?;
+
+        if cmd_matches.get_flag("validate") {
+            options.format.reject_tabular("render --validate")?;
+            let issues = crate::validate::validate(&value);
+            if options.format.is_structured() {
+                println!("{}", options.format.render_value(&issues)?);
+            } else if issues.is_empty() {
+                println!("'{}' matches the model", path);
+            } else {
+                for issue in issues.iter() {
+                    println!("{}", issue);
+                }
+            }
+
+            if !issues.is_empty() {
+                return Err(ExitError::new(
+                    "badInput",
+                    format!("Found {} problem(s) in '{}'", issues.len(), path),
+                )
+                .into());
+            }
+            return Ok(());
+        }
+
+        let result = serde_json::from_value::<LinksAPIResult>(value).map_err(|err| {
+            ExitError::new("badInput", format!("'{path}' is not a valid result: {err}"))
+        })?;
+        crate::output::print_result(options, &result, || {
+            println!("Input File: {path}");
+        })?;
+
+        Ok(())
+    }
+}
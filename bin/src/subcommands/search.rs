@@ -0,0 +1,107 @@
+use std::{collections::HashSet, str::FromStr};
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use odesli_rs::EntityUniqueId;
+
+use super::OdesliSubcommand;
+use crate::options::GlobalOptions;
+
+pub struct SearchSubcommand;
+
+#[async_trait::async_trait]
+impl OdesliSubcommand for SearchSubcommand {
+    fn name(&self) -> &'static str {
+        "search"
+    }
+
+    fn get_subcommand(&self) -> Command {
+        Command::new(self.name())
+            .about("search previously looked up entities, without calling the API")
+            .arg(
+                Arg::new("query")
+                    .help("What to search for, e.g. \"artist - title\"")
+                    .action(ArgAction::Set)
+                    .required(true)
+                    .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("limit")
+                    .short('l')
+                    .long("limit")
+                    .help("The maximum number of matches to print")
+                    .action(ArgAction::Set)
+                    .default_value("5")
+                    .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..))
+                    .num_args(1),
+            )
+    }
+
+    async fn handle_subcommand(
+        &self,
+        _final_cmd: &mut Command,
+        cmd_matches: &ArgMatches,
+        options: &GlobalOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        options.format.reject_tabular(self.name())?;
+        let query = cmd_matches.get_one::<String>("query").expect("Argument 'query' is required");
+        let limit = *cmd_matches.get_one::<usize>("limit").expect("Argument 'limit' has a default");
+
+        let catalog = options.require_catalog()?;
+        let index = catalog.search_index()?;
+
+        // Several entities of the same result usually match, only keep the
+        // best one of each result.
+        let mut seen_results = HashSet::new();
+        let mut matches = Vec::new();
+        for hit in index.search(query, index.len()) {
+            if matches.len() == limit {
+                break;
+            }
+            let Ok(unique_id) = EntityUniqueId::from_str(&hit.unique_id) else {
+                continue;
+            };
+            let Some(result) = catalog.result_for(&unique_id)? else {
+                continue;
+            };
+            if seen_results.insert(result.entity_unique_id.clone()) {
+                matches.push((hit, result));
+            }
+        }
+
+        if options.format.is_structured() {
+            let matches = matches
+                .iter()
+                .map(|(hit, result)| {
+                    serde_json::json!({
+                        "score": hit.score,
+                        "entityUniqueId": hit.unique_id,
+                        "title": hit.title,
+                        "artistName": hit.artist_name,
+                        "result": result,
+                    })
+                })
+                .collect::<Vec<_>>();
+            println!("{}", options.format.render_value(&matches)?);
+        } else if matches.is_empty() {
+            println!("No matches for {:?}", query);
+        } else {
+            for (num, (hit, result)) in matches.iter().enumerate() {
+                println!(
+                    "{}. {} - {} (score: {:.2})",
+                    num + 1,
+                    hit.artist_name.as_deref().unwrap_or("<NA>"),
+                    hit.title.as_deref().unwrap_or("<NA>"),
+                    hit.score
+                );
+                println!("  SongLink Page: {}", result.page_url);
+                for (platform, link) in result.links_by_platform.iter() {
+                    println!("  {}: {}", platform.display_name(), link.url);
+                }
+                println!();
+            }
+        }
+
+        Ok(())
+    }
+}
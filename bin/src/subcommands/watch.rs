@@ -0,0 +1,263 @@
+use std::time::Duration;
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use odesli_rs::{OdesliClient, ParsedInput};
+
+use super::{batch::DEFAULT_RATE_LIMIT, OdesliSubcommand};
+use crate::{
+    batch::RateLimiter,
+    errors::ExitError,
+    options::GlobalOptions,
+    utils::{format_duration, parse_duration},
+    watch::{Notification, PlatformLink, WatchState},
+};
+
+/// How long to wait between checks when neither `--interval` nor the
+/// configuration sets it.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How long to wait for the webhook to answer.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct WatchSubcommand;
+
+#[async_trait::async_trait]
+impl OdesliSubcommand for WatchSubcommand {
+    fn name(&self) -> &'static str {
+        "watch"
+    }
+
+    fn get_subcommand(&self) -> Command {
+        Command::new(self.name())
+            .about("check links periodically, notifying when platforms appear or disappear")
+            .arg(
+                Arg::new("input")
+                    .help("URLs, platform URIs or entity unique IDs, as accepted by get")
+                    .action(ArgAction::Append)
+                    .required(true)
+                    .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                    .num_args(1..),
+            )
+            .arg(
+                Arg::new("interval")
+                    .short('i')
+                    .long("interval")
+                    .help("How long to wait between checks, e.g. 30m or 1h [default: 1h]")
+                    .action(ArgAction::Set)
+                    .value_parser(parse_duration)
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("webhook")
+                    .long("webhook")
+                    .help("POST the changes as JSON to this URL, besides printing them")
+                    .action(ArgAction::Set)
+                    .value_parser(|url: &str| {
+                        odesli_rs::Url::parse(url)
+                            .map(|_| url.to_string())
+                            .map_err(|err| format!("Invalid URL '{url}': {err}"))
+                    })
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("rate-limit")
+                    .short('r')
+                    .long("rate-limit")
+                    .help(
+                        "The maximum number of requests per minute, 0 for no limit \
+                         [default: 10 without an API key, no limit with one]",
+                    )
+                    .action(ArgAction::Set)
+                    .value_parser(value_parser!(u32))
+                    .num_args(1),
+            )
+            .arg(
+                Arg::new("once")
+                    .long("once")
+                    .help("Check the links once and exit, e.g. to run from cron")
+                    .num_args(0)
+                    .action(ArgAction::SetTrue)
+                    .required(false),
+            )
+    }
+
+    async fn handle_subcommand(
+        &self,
+        _final_cmd: &mut Command,
+        cmd_matches: &ArgMatches,
+        options: &GlobalOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        options.format.reject_tabular(self.name())?;
+        let inputs = cmd_matches
+            .get_many::<String>("input")
+            .expect("Argument 'input' is required")
+            .map(|input| Ok((input.trim().to_string(), odesli_rs::parse_input(input)?)))
+            .collect::<Result<Vec<_>, odesli_rs::OdesliError>>()?;
+        let interval = cmd_matches
+            .get_one::<Duration>("interval")
+            .copied()
+            .or(options.watch_interval)
+            .unwrap_or(DEFAULT_INTERVAL);
+        if interval.is_zero() {
+            return Err(ExitError::new("badInput", "The interval must not be zero").into());
+        }
+        let rate_limit = match cmd_matches.get_one::<u32>("rate-limit") {
+            Some(rate_limit) => *rate_limit,
+            None if !options.has_api_key() => DEFAULT_RATE_LIMIT,
+            None => 0,
+        };
+        let webhook = match cmd_matches.get_one::<String>("webhook") {
+            Some(webhook) => Some(webhook.clone()),
+            None => match options.watch_webhook.as_ref() {
+                Some(webhook) => {
+                    odesli_rs::Url::parse(webhook).map_err(|err| {
+                        format!("Invalid webhook '{webhook}' in the configuration: {err}")
+                    })?;
+                    Some(webhook.clone())
+                }
+                None => None,
+            },
+        };
+
+        // Changes can only be seen in fresh results.
+        let client = crate::utils::build_fresh_odesli_client(options)?;
+        if options.dry_run {
+            for (_, input) in inputs.iter() {
+                crate::utils::print_dry_run(client.request_url_by_input(input)?);
+            }
+            return Ok(());
+        }
+
+        let path = options
+            .watch_path
+            .clone()
+            .ok_or("No file to store the watched links in, set path in the [watch] section")?;
+        let mut state = WatchState::load(path.clone())
+            .map_err(|err| format!("Failed to read '{}': {}", path.display(), err))?;
+        let http = reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build()?;
+        let rate_limiter = (rate_limit > 0).then(|| RateLimiter::per_minute(rate_limit));
+        let watcher = Watcher { options, client, webhook, http, rate_limiter };
+
+        if !cmd_matches.get_flag("once") && !options.format.is_structured() {
+            println!(
+                "Checking {} link(s) every {}, press Ctrl-C to stop",
+                inputs.len(),
+                format_duration(interval)
+            );
+        }
+        loop {
+            let failed = watcher.check(&inputs, &mut state).await?;
+            if cmd_matches.get_flag("once") {
+                if failed > 0 {
+                    return Err(
+                        format!("Failed to check {} of {} link(s)", failed, inputs.len()).into()
+                    );
+                }
+                return Ok(());
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
+
+struct Watcher<'a> {
+    options: &'a GlobalOptions,
+    client: OdesliClient,
+    webhook: Option<String>,
+    http: reqwest::Client,
+    rate_limiter: Option<RateLimiter>,
+}
+
+impl Watcher<'_> {
+    /// Look up all the `inputs` once, notify of their changes since the last
+    /// check and save the `state`. Lookup and webhook failures are only
+    /// reported, to try again at the next check; their number is returned.
+    async fn check(
+        &self,
+        inputs: &[(String, ParsedInput)],
+        state: &mut WatchState,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut failed = 0;
+        for (input, parsed) in inputs.iter() {
+            if let Some(rate_limiter) = self.rate_limiter.as_ref() {
+                rate_limiter.acquire().await;
+            }
+            let result = match self.client.get_by_input(parsed).await {
+                Ok(result) => result,
+                Err(error) => {
+                    eprintln!("Failed to check '{}': {}", input, error);
+                    failed += 1;
+                    continue;
+                }
+            };
+            crate::history::record_bulk(self.options, "watch", input, &result).await;
+
+            let mut pending = Vec::new();
+            match state.get(input) {
+                Some(snapshot) => {
+                    pending = snapshot.pending.clone();
+                    if let Some(notification) = Notification::new(input, &snapshot.result, &result)
+                    {
+                        self.print(&notification)?;
+                        if self.webhook.is_some() {
+                            pending.push(notification);
+                        }
+                    }
+                }
+                None if !self.options.format.is_structured() => println!(
+                    "Watching {}: {}, on {} platform(s)",
+                    input,
+                    crate::history::summarize(&result),
+                    result.links_by_platform.len()
+                ),
+                None => {}
+            }
+
+            // Changes are printed once, but sent again at the next checks
+            // until the webhook gets them.
+            let pending = self.deliver(pending).await;
+            if !pending.is_empty() {
+                failed += 1;
+            }
+            state.update(input, result, pending);
+        }
+
+        // Waiting for the lock of another process blocks.
+        tokio::task::block_in_place(|| state.save())
+            .map_err(|err| format!("Failed to write '{}': {}", state.path().display(), err))?;
+        Ok(failed)
+    }
+
+    fn print(&self, notification: &Notification) -> Result<(), Box<dyn std::error::Error>> {
+        let format = self.options.format;
+        if format.is_structured() {
+            println!("{}", format.render_value(notification)?);
+        } else {
+            println!("{} ({})", notification.summary, notification.input);
+            let describe =
+                |link: &PlatformLink| format!("{}: {}", link.platform.display_name(), link.url);
+            for link in notification.added.iter() {
+                println!("  + now on {}", describe(link));
+            }
+            for link in notification.removed.iter() {
+                println!("  - no longer on {}", describe(link));
+            }
+        }
+        Ok(())
+    }
+
+    /// Send the `pending` notifications to the webhook if any, oldest first.
+    /// Returns those it did not get, a failure being only reported.
+    async fn deliver(&self, mut pending: Vec<Notification>) -> Vec<Notification> {
+        let Some(webhook) = self.webhook.as_ref() else {
+            return Vec::new();
+        };
+        for index in 0..pending.len() {
+            if let Err(error) = pending[index].send(&self.http, webhook).await {
+                eprintln!("Failed to notify the webhook of '{}': {}", pending[index].input, error);
+                return pending.split_off(index);
+            }
+        }
+        Vec::new()
+    }
+}
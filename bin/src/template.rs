@@ -0,0 +1,423 @@
+//! A small template language to render a [`LinksAPIResult`].
+//!
+//! - `{name}` is replaced by the value of the variable `name`, or by nothing if
+//!   the value is missing.
+//! - `{#each links}...{/each}` repeats its body for each platform link, in the
+//!   order of [`Platform::iter`].
+//! - `{#if name}...{#else}...{/if}` renders its first branch if `name` has a
+//!   non-empty value and the (optional) second one otherwise. `{#if !name}`
+//!   negates the condition.
+//! - `{{` and `}}` are a literal `{` and `}`.
+//!
+//! The variables available everywhere are `title`, `artist`, `page_url`,
+//! `entity_unique_id`, `user_country` and `thumbnail_url` (those of the input
+//! entity), and `links.<platform>.<field>` for every platform, where `<field>`
+//! is one of [`LINK_FIELDS`]. Inside `{#each links}`, the fields of the current
+//! link are available directly, along with `platform`, `index` (starting at 1),
+//! `first` and `last`. Unknown variables are rejected when the template is
+//! parsed.
+
+use std::{collections::HashMap, str::FromStr};
+
+use odesli_rs::{LinksAPIResult, Platform};
+use strum::IntoEnumIterator;
+
+/// The variables describing a link, available as `links.<platform>.<field>`.
+pub const LINK_FIELDS: [&str; 6] = [
+    "url",
+    "native_app_uri_mobile",
+    "native_app_uri_desktop",
+    "entity_unique_id",
+    "title",
+    "artist",
+];
+
+const SUMMARY_FIELDS: [&str; 6] =
+    ["title", "artist", "page_url", "entity_unique_id", "user_country", "thumbnail_url"];
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Variable(String),
+    Each { body: Vec<Node> },
+    If { negated: bool, condition: String, then: Vec<Node>, otherwise: Vec<Node> },
+}
+
+/// A parsed template.
+#[derive(Debug)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+/// The blocks being parsed, innermost last.
+enum Block {
+    Each,
+    If { negated: bool, condition: String, then: Option<Vec<Node>> },
+}
+
+impl FromStr for Template {
+    type Err = String;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        let mut stack: Vec<(Block, Vec<Node>)> = Vec::new();
+        let mut nodes = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '}' => return Err("Unmatched '}', use '}}' for a literal '}'".to_string()),
+                '{' => {
+                    let mut tag = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => tag.push(c),
+                            None => return Err(format!("Unclosed tag '{{{tag}'")),
+                        }
+                    }
+                    let tag = tag.trim();
+
+                    if !text.is_empty() {
+                        nodes.push(Node::Text(std::mem::take(&mut text)));
+                    }
+
+                    if let Some(list) = tag.strip_prefix("#each ") {
+                        if list.trim() != "links" {
+                            return Err(format!(
+                                "Unknown list '{}', only 'links' exists",
+                                list.trim()
+                            ));
+                        }
+                        stack.push((Block::Each, std::mem::take(&mut nodes)));
+                    } else if let Some(condition) = tag.strip_prefix("#if ") {
+                        let condition = condition.trim();
+                        let (negated, condition) = match condition.strip_prefix('!') {
+                            Some(condition) => (true, condition.trim()),
+                            None => (false, condition),
+                        };
+                        check_variable(condition, &stack)?;
+                        let block =
+                            Block::If { negated, condition: condition.to_string(), then: None };
+                        stack.push((block, std::mem::take(&mut nodes)));
+                    } else if tag == "#else" {
+                        match stack.last_mut() {
+                            Some((Block::If { then: then @ None, .. }, _)) => {
+                                *then = Some(std::mem::take(&mut nodes));
+                            }
+                            _ => return Err("'{#else}' outside of '{#if}'".to_string()),
+                        }
+                    } else if tag == "/each" {
+                        match stack.pop() {
+                            Some((Block::Each, outer)) => {
+                                let body = std::mem::replace(&mut nodes, outer);
+                                nodes.push(Node::Each { body });
+                            }
+                            _ => return Err("'{/each}' without '{#each}'".to_string()),
+                        }
+                    } else if tag == "/if" {
+                        match stack.pop() {
+                            Some((Block::If { negated, condition, then }, outer)) => {
+                                let last = std::mem::replace(&mut nodes, outer);
+                                let (then, otherwise) = match then {
+                                    Some(then) => (then, last),
+                                    None => (last, Vec::new()),
+                                };
+                                nodes.push(Node::If { negated, condition, then, otherwise });
+                            }
+                            _ => return Err("'{/if}' without '{#if}'".to_string()),
+                        }
+                    } else if tag.is_empty() || tag.starts_with(['#', '/']) {
+                        return Err(format!("Unknown tag '{{{tag}}}'"));
+                    } else {
+                        check_variable(tag, &stack)?;
+                        nodes.push(Node::Variable(tag.to_string()));
+                    }
+                }
+                c => text.push(c),
+            }
+        }
+
+        if let Some((block, _)) = stack.last() {
+            return Err(match block {
+                Block::Each => "'{#each}' is never closed with '{/each}'".to_string(),
+                Block::If { .. } => "'{#if}' is never closed with '{/if}'".to_string(),
+            });
+        }
+        if !text.is_empty() {
+            nodes.push(Node::Text(text));
+        }
+
+        Ok(Self { nodes })
+    }
+}
+
+/// Fail on the variables that are not known at this point of the template,
+/// so that a typo is reported even if the results never reach it.
+fn check_variable(name: &str, stack: &[(Block, Vec<Node>)]) -> Result<(), String> {
+    let in_each = stack.iter().any(|(block, _)| matches!(block, Block::Each));
+    if is_known_variable(name) || (in_each && is_item_variable(name)) {
+        Ok(())
+    } else {
+        Err(format!("Unknown template variable '{name}'"))
+    }
+}
+
+/// The variables in scope: those of the result and, inside `{#each links}`,
+/// those of the current link.
+struct Scopes<'a> {
+    root: &'a HashMap<String, String>,
+    item: Option<&'a HashMap<String, String>>,
+}
+
+impl Scopes<'_> {
+    fn get(&self, name: &str) -> Result<Option<&str>, String> {
+        if let Some(item) = self.item {
+            if let Some(value) = item.get(name) {
+                return Ok(Some(value));
+            }
+            if is_item_variable(name) {
+                return Ok(None);
+            }
+        }
+        if let Some(value) = self.root.get(name) {
+            return Ok(Some(value));
+        }
+        if is_known_variable(name) {
+            Ok(None)
+        } else {
+            Err(format!("Unknown template variable '{name}'"))
+        }
+    }
+}
+
+/// Whether `name` is a variable of the current link inside `{#each links}`.
+fn is_item_variable(name: &str) -> bool {
+    LINK_FIELDS.contains(&name) || ["platform", "index", "first", "last"].contains(&name)
+}
+
+fn is_known_variable(name: &str) -> bool {
+    if SUMMARY_FIELDS.contains(&name) {
+        return true;
+    }
+    let mut parts = name.split('.');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some("links"), Some(platform), None, None) => Platform::from_str(platform).is_ok(),
+        (Some("links"), Some(platform), Some(field), None) => {
+            Platform::from_str(platform).is_ok() && LINK_FIELDS.contains(&field)
+        }
+        _ => false,
+    }
+}
+
+impl Template {
+    /// Read a template from a file.
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let template = std::fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read template '{path}': {err}"))?;
+        Self::from_str(&template)
+    }
+
+    /// Parse a template given on the command line, where `\n` and `\t` stand
+    /// for a newline and a tab.
+    pub fn from_inline(template: &str) -> Result<Self, String> {
+        Self::from_str(&template.replace("\\n", "\n").replace("\\t", "\t"))
+    }
+
+    pub fn render(&self, result: &LinksAPIResult) -> Result<String, String> {
+        let root = root_variables(result);
+        let links = Platform::iter()
+            .filter(|platform| result.links_by_platform.contains_key(platform))
+            .collect::<Vec<_>>();
+        let items = links
+            .iter()
+            .enumerate()
+            .map(|(index, platform)| {
+                let mut item = link_variables(result, platform);
+                item.insert("platform".to_string(), platform.as_str().to_string());
+                item.insert("index".to_string(), (index + 1).to_string());
+                if index == 0 {
+                    item.insert("first".to_string(), "true".to_string());
+                }
+                if index + 1 == links.len() {
+                    item.insert("last".to_string(), "true".to_string());
+                }
+                item
+            })
+            .collect::<Vec<_>>();
+
+        let mut output = String::new();
+        render_nodes(&self.nodes, &Scopes { root: &root, item: None }, &items, &mut output)?;
+        Ok(output)
+    }
+}
+
+fn render_nodes(
+    nodes: &[Node],
+    scopes: &Scopes,
+    items: &[HashMap<String, String>],
+    output: &mut String,
+) -> Result<(), String> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Variable(name) => output.push_str(scopes.get(name)?.unwrap_or_default()),
+            Node::Each { body } => {
+                for item in items {
+                    render_nodes(
+                        body,
+                        &Scopes { root: scopes.root, item: Some(item) },
+                        items,
+                        output,
+                    )?;
+                }
+            }
+            Node::If { negated, condition, then, otherwise } => {
+                let truthy = scopes.get(condition)?.is_some_and(|value| !value.is_empty());
+                let branch = if truthy != *negated { then } else { otherwise };
+                render_nodes(branch, scopes, items, output)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn root_variables(result: &LinksAPIResult) -> HashMap<String, String> {
+    let mut variables = HashMap::new();
+    variables.insert("page_url".to_string(), result.page_url.clone());
+    variables.insert("entity_unique_id".to_string(), result.entity_unique_id.clone());
+    variables.insert("user_country".to_string(), result.user_country.clone());
+
+    if let Some(entity) = result.entities_by_unique_id.get(&result.entity_unique_id) {
+        let fields = [
+            ("title", &entity.title),
+            ("artist", &entity.artist_name),
+            ("thumbnail_url", &entity.thumbnail_url),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value {
+                variables.insert(name.to_string(), value.clone());
+            }
+        }
+    }
+
+    for platform in result.links_by_platform.keys() {
+        for (field, value) in link_variables(result, platform) {
+            variables.insert(format!("links.{}.{}", platform.as_str(), field), value);
+        }
+        // Lets `{#if links.<platform>}` check whether the platform has a match.
+        variables.insert(format!("links.{}", platform.as_str()), "true".to_string());
+    }
+
+    variables
+}
+
+fn link_variables(result: &LinksAPIResult, platform: &Platform) -> HashMap<String, String> {
+    let mut variables = HashMap::new();
+    let Some(link) = result.get_platform_url(platform) else {
+        return variables;
+    };
+    let entity = result.get_platform_entity(platform);
+
+    let fields = [
+        ("url", Some(&link.url)),
+        ("native_app_uri_mobile", link.native_app_uri_mobile.as_ref()),
+        ("native_app_uri_desktop", link.native_app_uri_desktop.as_ref()),
+        ("entity_unique_id", Some(&link.entity_unique_id)),
+        ("title", entity.and_then(|entity| entity.title.as_ref())),
+        ("artist", entity.and_then(|entity| entity.artist_name.as_ref())),
+    ];
+    for (name, value) in fields {
+        if let Some(value) = value {
+            variables.insert(name.to_string(), value.clone());
+        }
+    }
+    variables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ResultBuilder;
+
+    fn result() -> LinksAPIResult {
+        ResultBuilder::new("SPOTIFY_SONG::1")
+            .link(Platform::Deezer, "DEEZER_SONG::2", "https://deezer.com/2")
+            .link(Platform::Spotify, "SPOTIFY_SONG::1", "https://spotify.com/1")
+            .entity("SPOTIFY_SONG::1", "Sugar", "Maroon 5")
+            .build()
+    }
+
+    fn render(template: &str) -> Result<String, String> {
+        Template::from_inline(template)?.render(&result())
+    }
+
+    #[test]
+    fn variables_and_escapes() {
+        assert_eq!(
+            render("{title} by {artist} {{{user_country}}}\\n{links.spotify.url}").unwrap(),
+            "Sugar by Maroon 5 {US}\nhttps://spotify.com/1"
+        );
+        // Known but missing variables render as nothing.
+        assert_eq!(render("[{thumbnail_url}{links.tidal.url}]").unwrap(), "[]");
+    }
+
+    #[test]
+    fn each_follows_the_platform_order() {
+        assert_eq!(
+            render("{#each links}{index}.{platform}={url}{#if !last},{/if}{/each}").unwrap(),
+            "1.spotify=https://spotify.com/1,2.deezer=https://deezer.com/2"
+        );
+        // The variables of the result stay available inside the loop.
+        assert_eq!(
+            render("{#each links}{#if first}{page_url}{/if}{/each}").unwrap(),
+            "https://song.link/s/1"
+        );
+    }
+
+    #[test]
+    fn conditions() {
+        assert_eq!(render("{#if links.spotify}yes{#else}no{/if}").unwrap(), "yes");
+        assert_eq!(render("{#if links.tidal}yes{#else}no{/if}").unwrap(), "no");
+        assert_eq!(render("{#if !links.tidal}missing{/if}").unwrap(), "missing");
+    }
+
+    #[test]
+    fn unknown_variables_are_rejected_when_parsing() {
+        for template in ["{tilte}", "{#if links.spotify.uri}{/if}", "{links.nowhere.url}"] {
+            let err = Template::from_inline(template).unwrap_err();
+            assert!(err.starts_with("Unknown template variable"), "{template}: {err}");
+        }
+        // Even in a branch that would not be rendered.
+        assert!(Template::from_inline("{#if !title}{artst}{/if}").is_err());
+        // The variables of a link only exist inside {#each links}.
+        assert!(Template::from_inline("{url}").is_err());
+        assert!(Template::from_inline("{#each links}{url}{/each}").is_ok());
+    }
+
+    #[test]
+    fn syntax_errors() {
+        for template in [
+            "{title",
+            "title}",
+            "{#each links}",
+            "{#if title}",
+            "{/if}",
+            "{/each}",
+            "{#else}",
+            "{#each albums}{/each}",
+            "{#unknown}",
+            "{}",
+        ] {
+            assert!(Template::from_inline(template).is_err(), "{template}");
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! Pretty printing of results for people, with aligned columns, colors and
+//! clickable links when the terminal supports them.
+
+use std::io::IsTerminal;
+
+use clap::ValueEnum;
+use odesli_rs::{LinksAPIResult, Platform};
+
+const BOLD: &str = "1";
+const DIM: &str = "2";
+const CYAN: &str = "36";
+
+/// URLs and names are not truncated to fewer characters than this, however
+/// narrow the terminal is.
+const MIN_WIDTH: usize = 24;
+
+/// When to color the output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Color when printing to a terminal, unless `NO_COLOR` is set.
+    #[default]
+    Auto,
+    /// Always color.
+    Always,
+    /// Never color.
+    Never,
+}
+
+/// What the standard output supports.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Terminal {
+    /// Color with ANSI escape codes.
+    pub color: bool,
+    /// Make URLs clickable with OSC 8 escape codes.
+    pub hyperlinks: bool,
+    /// Truncate URLs and names to fit in this many columns, if known.
+    pub width: Option<usize>,
+}
+
+impl Terminal {
+    /// Detect what the standard output supports. Colors follow `color`, and
+    /// truncation is only used when printing to a terminal. Hyperlinks are
+    /// escape codes too, so they need both.
+    pub fn detect(color: ColorChoice) -> Self {
+        let is_terminal = std::io::stdout().is_terminal()
+            && std::env::var_os("TERM").is_none_or(|term| term != "dumb");
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let color = match color {
+            ColorChoice::Auto => is_terminal && !no_color,
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        };
+
+        Terminal {
+            color,
+            hyperlinks: is_terminal && color,
+            width: is_terminal
+                .then(terminal_size::terminal_size)
+                .flatten()
+                .map(|(width, _)| width.0 as usize),
+        }
+    }
+
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color {
+            format!("\x1b[{style}m{text}\x1b[0m")
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Truncate `text` to the columns left after `indent`, keeping at least
+    /// `min_width` characters.
+    fn fit(&self, text: &str, indent: usize, min_width: usize) -> String {
+        match self.width {
+            Some(width) => truncate(text, width.saturating_sub(indent).max(min_width)),
+            None => text.to_string(),
+        }
+    }
+
+    /// Print `url` in the columns left after `indent`, linking to the full URL
+    /// if it is truncated.
+    fn url(&self, url: &str, indent: usize) -> String {
+        let text = self.fit(url, indent, MIN_WIDTH);
+        if self.hyperlinks {
+            format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+        } else {
+            text
+        }
+    }
+
+    /// Print `value` in the columns left after `indent`, or `<NA>` if missing.
+    fn or_na(&self, value: Option<&str>, indent: usize) -> String {
+        match value {
+            Some(value) => self.fit(value, indent, MIN_WIDTH),
+            None => self.paint(DIM, "<NA>"),
+        }
+    }
+}
+
+/// Shorten `text` to `width` characters, ending it with an ellipsis if it was
+/// cut.
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut truncated = text.chars().take(width.saturating_sub(1)).collect::<String>();
+    truncated.push('…');
+    truncated
+}
+
+fn pad(text: &str, width: usize) -> String {
+    format!("{text:<width$}")
+}
+
+/// Pretty print `result`, listing the links of the `preferred_platforms`
+/// first.
+pub fn print_result(
+    terminal: &Terminal,
+    result: &LinksAPIResult,
+    preferred_platforms: &[Platform],
+) {
+    let label = "SongLink Page: ";
+    println!("{}{}", terminal.paint(BOLD, label), terminal.url(&result.page_url, label.len()));
+
+    let mut links = result.links_by_platform.iter().collect::<Vec<_>>();
+    links.sort_by_key(|(platform, _)| {
+        preferred_platforms
+            .iter()
+            .position(|preferred| preferred == *platform)
+            .unwrap_or(usize::MAX)
+    });
+    let headers = ["PLATFORM", "ENTITY", "URL"];
+    let name_width = links
+        .iter()
+        .map(|(platform, _)| platform.display_name().chars().count())
+        .chain([headers[0].len()])
+        .max()
+        .unwrap_or_default();
+    let id_width = links
+        .iter()
+        .map(|(_, link)| link.entity_unique_id.to_string().chars().count())
+        .chain([headers[1].len()])
+        .max()
+        .unwrap_or_default();
+    let url_indent = 2 + name_width + 2 + id_width + 2;
+
+    println!("\n{}", terminal.paint(BOLD, "Links"));
+    println!(
+        "  {}",
+        terminal.paint(
+            DIM,
+            &format!(
+                "{}  {}  {}",
+                pad(headers[0], name_width),
+                pad(headers[1], id_width),
+                headers[2]
+            )
+        )
+    );
+    for (platform, link) in links {
+        println!(
+            "  {}  {}  {}",
+            terminal.paint(CYAN, &pad(platform.display_name(), name_width)),
+            pad(&link.entity_unique_id.to_string(), id_width),
+            terminal.url(&link.url, url_indent)
+        );
+    }
+
+    println!("\n{}", terminal.paint(BOLD, "Details"));
+    for (unique_id, entity) in result.entities_by_unique_id.iter() {
+        let name = entity.api_provider.platform().display_name();
+        println!("  {}  {}", terminal.paint(CYAN, name), terminal.paint(DIM, unique_id));
+
+        let thumbnail = match (entity.thumbnail_width, entity.thumbnail_height) {
+            (Some(width), Some(height)) => format!("Thumbnail ({width}x{height})"),
+            _ => "Thumbnail".to_string(),
+        };
+        let label_width = thumbnail.len();
+        let indent = 4 + label_width + 2;
+        for (label, value) in [
+            ("Title", terminal.or_na(entity.title.as_deref(), indent)),
+            ("Artist(s)", terminal.or_na(entity.artist_name.as_deref(), indent)),
+            (
+                &thumbnail,
+                match entity.thumbnail_url.as_deref() {
+                    Some(url) => terminal.url(url, indent),
+                    None => terminal.or_na(None, indent),
+                },
+            ),
+        ] {
+            println!("    {}  {}", terminal.paint(DIM, &pad(label, label_width)), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_counts_characters() {
+        assert_eq!(truncate("Sugar", 5), "Sugar");
+        assert_eq!(truncate("Sugar", 4), "Sug…");
+        assert_eq!(truncate("Beyoncé Knowles", 8), "Beyoncé…");
+        assert_eq!(truncate("Sugar", 1), "…");
+        assert_eq!(truncate("Sugar", 0), "…");
+    }
+
+    #[test]
+    fn fit_keeps_a_minimum_width() {
+        let text = "https://open.spotify.com/track/7CNUefGBVLn4cLoYv3ej8x";
+        let unknown = Terminal { width: None, ..Terminal::default() };
+        assert_eq!(unknown.fit(text, 10, MIN_WIDTH), text);
+
+        let wide = Terminal { width: Some(80), ..Terminal::default() };
+        assert_eq!(wide.fit(text, 10, MIN_WIDTH), text);
+        assert_eq!(wide.fit(text, 40, MIN_WIDTH).chars().count(), 40);
+
+        let narrow = Terminal { width: Some(20), ..Terminal::default() };
+        assert_eq!(narrow.fit(text, 10, MIN_WIDTH).chars().count(), MIN_WIDTH);
+        assert_eq!(narrow.fit(text, 30, MIN_WIDTH).chars().count(), MIN_WIDTH);
+    }
+
+    #[test]
+    fn escapes_are_only_added_when_supported() {
+        let url = "https://song.link/s/1";
+        let plain = Terminal::default();
+        assert_eq!(plain.url(url, 0), url);
+        assert_eq!(plain.paint(BOLD, "Sugar"), "Sugar");
+        assert_eq!(plain.or_na(None, 0), "<NA>");
+
+        let rich = Terminal { color: true, hyperlinks: true, width: None };
+        assert_eq!(rich.url(url, 0), format!("\x1b]8;;{url}\x1b\\{url}\x1b]8;;\x1b\\"));
+        assert_eq!(rich.paint(BOLD, "Sugar"), "\x1b[1mSugar\x1b[0m");
+    }
+}
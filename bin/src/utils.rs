@@ -1,44 +1,187 @@
-use odesli_rs::{LinksAPIResult, OdesliClient};
+use std::time::Duration;
 
-pub fn build_odesli_client(api_key: Option<String>) -> OdesliClient {
-    let mut builder = odesli_rs::ClientBuilder::default();
-    if let Some(api_key) = api_key {
+use odesli_rs::{ClientBuilder, OdesliClient, OdesliError};
+
+use crate::options::GlobalOptions;
+
+pub fn build_odesli_client(options: &GlobalOptions) -> Result<OdesliClient, OdesliError> {
+    Ok(odesli_client_builder(options)?.build())
+}
+
+/// A client set up as requested by `options`, but always calling the API, for
+/// the commands whose point is to get fresh results. The results are still
+/// recorded in the catalog.
+pub fn build_fresh_odesli_client(options: &GlobalOptions) -> Result<OdesliClient, OdesliError> {
+    Ok(client_builder(options, false)?.build())
+}
+
+/// A client builder set up as requested by `options`, for callers that need
+/// to adjust it further.
+pub fn odesli_client_builder(options: &GlobalOptions) -> Result<ClientBuilder, OdesliError> {
+    client_builder(options, options.prefer_catalog)
+}
+
+/// A client builder set up as requested by `options`, looking results up in
+/// the catalog first if `prefer_catalog`.
+fn client_builder(
+    options: &GlobalOptions,
+    prefer_catalog: bool,
+) -> Result<ClientBuilder, OdesliError> {
+    let mut builder = ClientBuilder::default();
+    if let Some(api_key) = options.resolve_api_key()? {
         builder = builder.with_api_key(api_key);
     }
-    builder.build()
+    if let Some(country) = options.country.as_ref() {
+        builder = builder.with_user_country(country.to_string());
+    }
+    if let Some(base_url) = options.base_url.as_ref() {
+        builder = builder.with_base_url(base_url.to_string());
+    }
+    match options.open_catalog() {
+        Ok(Some(catalog)) => {
+            builder = builder.with_catalog(catalog);
+            if prefer_catalog {
+                builder = builder.with_catalog_lookup(options.catalog_max_age);
+            }
+        }
+        Ok(None) => {}
+        Err(error) => eprintln!("Not using the catalog: {}", error),
+    }
+    Ok(builder)
+}
+
+/// Print the request to `url` instead of sending it, with the API key
+/// redacted.
+pub fn print_dry_run(mut url: odesli_rs::Url) {
+    odesli_rs::redact_url(&mut url);
+    println!("GET {}", url);
+}
+
+/// A short, stable name for the kind of `error`, for machine readable output.
+pub fn error_kind(error: &OdesliError) -> &'static str {
+    match error {
+        OdesliError::ReqwestError(_) => "network",
+        OdesliError::Non200StatusCode { status_code, .. } => match status_code.as_u16() {
+            400 => "badInput",
+            404 => "notFound",
+            429 => "rateLimited",
+            _ => "api",
+        },
+        OdesliError::ParseError { .. } => "parseError",
+        OdesliError::UnknownEntityType(_)
+        | OdesliError::UnknownPlatform(_)
+        | OdesliError::UnknownAPIProvider(_)
+        | OdesliError::InvalidEntityUniqueId(_)
+        | OdesliError::UnrecognizedInput(_)
+        | OdesliError::InvalidBaseUrl(_) => "badInput",
+        _ => "other",
+    }
+}
+
+/// Describe `error` as a JSON object with its kind, message, and the status
+/// code and body of the response, if any.
+pub fn error_to_json(error: &OdesliError) -> serde_json::Value {
+    let (status_code, body) = match error {
+        OdesliError::Non200StatusCode { status_code, body } => {
+            (Some(status_code.as_u16()), Some(body))
+        }
+        OdesliError::ParseError { body, .. } => (None, Some(body)),
+        _ => (None, None),
+    };
+
+    serde_json::json!({
+        "kind": error_kind(error),
+        "message": error.to_string(),
+        "statusCode": status_code,
+        "body": body,
+    })
+}
+
+/// Parse a duration like `90s`, `15m`, `12h`, `7d` or `2w`. A bare number is
+/// a number of seconds.
+pub fn parse_duration(duration: &str) -> Result<Duration, String> {
+    let duration = duration.trim();
+    let split = duration.find(|c: char| !c.is_ascii_digit()).unwrap_or(duration.len());
+    let (amount, unit) = duration.split_at(split);
+    let amount = amount.parse::<u64>().map_err(|_| format!("Invalid duration '{duration}'"))?;
+    let seconds = match unit.trim() {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "Invalid duration '{duration}', use one of the units s, m, h, d or w"
+            ))
+        }
+    };
+    amount
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("Duration '{duration}' is too long"))
 }
 
-pub fn pretty_print_api_result(result: &LinksAPIResult) {
-    println!("SongLink Page: {}", result.page_url);
+/// Format a duration the way [`parse_duration`] reads it, in the largest unit
+/// that divides it.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let units = [("w", 7 * 24 * 60 * 60), ("d", 24 * 60 * 60), ("h", 60 * 60), ("m", 60)];
+    for (unit, size) in units {
+        if seconds > 0 && seconds % size == 0 {
+            return format!("{}{}", seconds / size, unit);
+        }
+    }
+    format!("{seconds}s")
+}
 
-    println!("\n---");
-    println!("Links From various platforms:");
-    for (platform, link) in result.links_by_platform.iter() {
-        println!("  {:?} ({}): {}", platform, link.entity_unique_id, link.url);
+/// Format `age` roughly, in its largest whole unit, e.g. `3h` for 3h25m.
+pub fn format_age(age: Duration) -> String {
+    let seconds = age.as_secs();
+    let units = [("w", 7 * 24 * 60 * 60), ("d", 24 * 60 * 60), ("h", 60 * 60), ("m", 60)];
+    for (unit, size) in units {
+        if seconds >= size {
+            return format!("{}{}", seconds / size, unit);
+        }
     }
-    println!("---");
+    format!("{seconds}s")
+}
 
-    println!("\n---");
-    println!("Details from various platforms:");
-    for entity in result.entities_by_unique_id.values() {
-        let platform = &entity.api_provider;
-        println!("\n  {:?}:", platform);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let title = if let Some(title) = &entity.title { title } else { "<NA>" };
-        println!("    Title: {}", title);
+    #[test]
+    fn durations_are_parsed() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration(" 90s "), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("15m"), Ok(Duration::from_secs(15 * 60)));
+        assert_eq!(parse_duration("2w"), Ok(Duration::from_secs(2 * 7 * 24 * 60 * 60)));
+        for duration in ["", "m", "1y", "-1h", "1.5h", "1h30m"] {
+            assert!(parse_duration(duration).is_err(), "{duration:?}");
+        }
+    }
 
-        let artists = if let Some(artists) = &entity.artist_name { artists } else { "<NA>" };
-        println!("    Artist(s): {}", artists);
+    #[test]
+    fn overflowing_durations_are_rejected() {
+        assert_eq!(parse_duration(&u64::MAX.to_string()), Ok(Duration::from_secs(u64::MAX)));
+        assert!(parse_duration(&format!("{}m", u64::MAX)).is_err());
+        assert!(parse_duration("99999999999999999999s").is_err());
+    }
+
+    #[test]
+    fn durations_round_trip() {
+        for duration in ["0s", "45s", "90s", "15m", "36h", "1d", "10d", "2w"] {
+            assert_eq!(format_duration(parse_duration(duration).unwrap()), duration);
+        }
+        assert_eq!(format_duration(Duration::from_secs(7 * 24 * 60 * 60)), "1w");
+        assert_eq!(format_duration(Duration::from_millis(1500)), "1s");
+    }
 
-        let (thumbnail_url, thumbnail_width, thumbnail_height) =
-            if let (Some(url), Some(width), Some(height)) =
-                (&entity.thumbnail_url, entity.thumbnail_width, entity.thumbnail_height)
-            {
-                (url.as_str(), width, height)
-            } else {
-                ("<NA>", 0u64, 0u64)
-            };
-        println!("    Thumbnail({}x{}): {}", thumbnail_width, thumbnail_height, thumbnail_url);
+    #[test]
+    fn ages_are_rounded_down() {
+        assert_eq!(format_age(Duration::from_secs(3 * 60 * 60 + 25 * 60)), "3h");
+        assert_eq!(format_age(Duration::from_secs(59)), "59s");
+        assert_eq!(format_age(Duration::from_secs(8 * 24 * 60 * 60)), "1w");
     }
-    println!("---");
 }
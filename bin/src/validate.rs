@@ -0,0 +1,205 @@
+//! Checks of saved results against the model of [`LinksAPIResult`], without
+//! calling the API.
+
+use std::str::FromStr;
+
+use odesli_rs::{APIProvider, EntityType, LinksAPIResult, Platform};
+use serde::Serialize;
+use serde_json::Value;
+
+const RESULT_FIELDS: [&str; 5] =
+    ["entityUniqueId", "userCountry", "pageUrl", "linksByPlatform", "entitiesByUniqueId"];
+const LINK_FIELDS: [&str; 4] =
+    ["entityUniqueId", "url", "nativeAppUriMobile", "nativeAppUriDesktop"];
+const ENTITY_FIELDS: [&str; 9] = [
+    "id",
+    "type",
+    "title",
+    "artistName",
+    "thumbnailUrl",
+    "thumbnailWidth",
+    "thumbnailHeight",
+    "apiProvider",
+    "platforms",
+];
+
+/// A problem found in a saved result.
+#[derive(Debug, Serialize)]
+pub struct Issue {
+    /// Where the problem is, as a dot separated path of keys.
+    pub path: String,
+    pub problem: String,
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.problem)
+        } else {
+            write!(f, "{}: {}", self.path, self.problem)
+        }
+    }
+}
+
+/// List the unknown fields, platforms, API providers and entity types of a
+/// saved result, and whether it fails to deserialize into a
+/// [`LinksAPIResult`].
+pub fn validate(value: &Value) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut issue = |path: String, problem: String| issues.push(Issue { path, problem });
+
+    let Some(result) = value.as_object() else {
+        issue(String::new(), "not a JSON object".to_string());
+        return issues;
+    };
+    for (key, value) in result {
+        if !RESULT_FIELDS.contains(&key.as_str()) {
+            issue(key.clone(), "unknown field".to_string());
+            continue;
+        }
+        let Some(children) = value.as_object() else {
+            continue;
+        };
+
+        for (child_key, child) in children {
+            let path = format!("{key}.{child_key}");
+            let known_fields = match key.as_str() {
+                "linksByPlatform" => {
+                    if Platform::from_str(child_key).is_err() {
+                        issue(path.clone(), format!("unknown platform '{child_key}'"));
+                    }
+                    &LINK_FIELDS[..]
+                }
+                "entitiesByUniqueId" => &ENTITY_FIELDS[..],
+                _ => continue,
+            };
+            let Some(fields) = child.as_object() else {
+                continue;
+            };
+
+            for (field, value) in fields {
+                let path = format!("{path}.{field}");
+                if !known_fields.contains(&field.as_str()) {
+                    issue(path, "unknown field".to_string());
+                    continue;
+                }
+                if key != "entitiesByUniqueId" {
+                    continue;
+                }
+                match (field.as_str(), value) {
+                    ("apiProvider", Value::String(provider))
+                        if APIProvider::from_str(provider).is_err() =>
+                    {
+                        issue(path, format!("unknown API provider '{provider}'"))
+                    }
+                    ("type", Value::String(entity_type))
+                        if EntityType::from_str(entity_type).is_err() =>
+                    {
+                        issue(path, format!("unknown entity type '{entity_type}'"))
+                    }
+                    ("platforms", Value::Array(platforms)) => {
+                        for (index, platform) in platforms.iter().enumerate() {
+                            let Some(platform) = platform.as_str() else {
+                                continue;
+                            };
+                            if Platform::from_str(platform).is_err() {
+                                let path = format!("{path}.{index}");
+                                issue(path, format!("unknown platform '{platform}'"));
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    if let Err(err) = serde_json::from_value::<LinksAPIResult>(value.clone()) {
+        issue(String::new(), format!("does not deserialize into a result: {err}"));
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn valid() -> Value {
+        json!({
+            "entityUniqueId": "SPOTIFY_SONG::1",
+            "userCountry": "US",
+            "pageUrl": "https://song.link/s/1",
+            "linksByPlatform": {
+                "spotify": { "entityUniqueId": "SPOTIFY_SONG::1", "url": "https://spotify.com/1" },
+            },
+            "entitiesByUniqueId": {
+                "SPOTIFY_SONG::1": {
+                    "id": "1",
+                    "type": "song",
+                    "apiProvider": "spotify",
+                    "platforms": ["spotify"],
+                },
+            },
+        })
+    }
+
+    /// The paths of the issues found in `value`, the deserialization failure
+    /// being at the empty path.
+    fn issue_paths(value: &Value) -> Vec<String> {
+        validate(value).into_iter().map(|issue| issue.path).collect()
+    }
+
+    #[test]
+    fn valid_results_have_no_issues() {
+        assert!(validate(&valid()).is_empty());
+    }
+
+    #[test]
+    fn unknown_fields() {
+        let mut value = valid();
+        value["extra"] = json!(1);
+        value["linksByPlatform"]["spotify"]["extra"] = json!(1);
+        value["entitiesByUniqueId"]["SPOTIFY_SONG::1"]["extra"] = json!(1);
+        // Unknown fields are ignored when deserializing.
+        assert_eq!(
+            issue_paths(&value),
+            ["linksByPlatform.spotify.extra", "entitiesByUniqueId.SPOTIFY_SONG::1.extra", "extra",]
+        );
+    }
+
+    #[test]
+    fn unknown_values() {
+        let mut value = valid();
+        value["linksByPlatform"]["myspace"] = json!({ "entityUniqueId": "x", "url": "x" });
+        let entity = &mut value["entitiesByUniqueId"]["SPOTIFY_SONG::1"];
+        entity["type"] = json!("podcast");
+        entity["apiProvider"] = json!("myspace");
+        entity["platforms"] = json!(["spotify", "myspace"]);
+
+        let issues = validate(&value);
+        let problems = issues.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            problems[..4],
+            [
+                "linksByPlatform.myspace: unknown platform 'myspace'",
+                "entitiesByUniqueId.SPOTIFY_SONG::1.type: unknown entity type 'podcast'",
+                "entitiesByUniqueId.SPOTIFY_SONG::1.apiProvider: unknown API provider 'myspace'",
+                "entitiesByUniqueId.SPOTIFY_SONG::1.platforms.1: unknown platform 'myspace'",
+            ]
+        );
+        assert_eq!(issues.len(), 5);
+        assert!(problems[4].starts_with("does not deserialize into a result"), "{}", problems[4]);
+    }
+
+    #[test]
+    fn malformed_results() {
+        assert_eq!(validate(&json!([])).len(), 1);
+        assert_eq!(validate(&json!([]))[0].to_string(), "not a JSON object");
+
+        let mut value = valid();
+        value.as_object_mut().unwrap().remove("pageUrl");
+        assert_eq!(issue_paths(&value), [""]);
+    }
+}
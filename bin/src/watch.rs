@@ -0,0 +1,238 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use odesli_rs::{Change, LinksAPIResult, Platform};
+use serde::{Deserialize, Serialize};
+
+use crate::{history::now, lock::FileLock};
+
+/// What `watch` last saw for a link.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    /// When the link was last checked, in seconds since the Unix epoch.
+    pub checked_at: u64,
+    pub result: LinksAPIResult,
+    /// The changes already printed but not delivered to the webhook yet,
+    /// oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pending: Vec<Notification>,
+}
+
+/// The last results of the watched links, stored as a JSON object keyed by
+/// the inputs given to `watch`, so that it can pick up where it left off.
+///
+/// Several `watch` processes may share the file, each saving only the links it
+/// checked.
+pub struct WatchState {
+    path: PathBuf,
+    snapshots: BTreeMap<String, Snapshot>,
+    /// The inputs updated since the state was loaded or saved.
+    updated: BTreeSet<String>,
+}
+
+impl WatchState {
+    /// Read the state at `path`, empty if the file does not exist yet.
+    pub fn load(path: PathBuf) -> std::io::Result<Self> {
+        let snapshots = read_snapshots(&path)?;
+        Ok(Self { path, snapshots, updated: BTreeSet::new() })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The last snapshot of `input`, if it was watched before.
+    pub fn get(&self, input: &str) -> Option<&Snapshot> {
+        self.snapshots.get(input)
+    }
+
+    /// Remember `result` as the latest of `input`, with the notifications
+    /// still `pending` for the webhook.
+    pub fn update(&mut self, input: &str, result: LinksAPIResult, pending: Vec<Notification>) {
+        let snapshot = Snapshot { checked_at: now().as_secs(), result, pending };
+        self.snapshots.insert(input.to_string(), snapshot);
+        self.updated.insert(input.to_string());
+    }
+
+    /// Write the updated snapshots into the file, keeping those other
+    /// processes saved since it was read. The file is written through a
+    /// temporary one so that an interrupted write does not lose it.
+    ///
+    /// Waiting for the lock of another process blocks, so this must run on a
+    /// thread where blocking is allowed.
+    pub fn save(&mut self) -> std::io::Result<()> {
+        let _lock = FileLock::acquire(&self.path)?;
+        let mut snapshots = read_snapshots(&self.path)?;
+        for input in std::mem::take(&mut self.updated) {
+            if let Some(snapshot) = self.snapshots.get(&input) {
+                snapshots.insert(input, snapshot.clone());
+            }
+        }
+        self.snapshots = snapshots;
+
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        let temporary = Path::new(&temporary);
+
+        let mut file = File::create(temporary)?;
+        writeln!(file, "{}", serde_json::to_string_pretty(&self.snapshots)?)?;
+        file.sync_all()?;
+        std::fs::rename(temporary, &self.path)
+    }
+}
+
+/// The snapshots saved at `path`, none if the file does not exist yet.
+fn read_snapshots(path: &Path) -> std::io::Result<BTreeMap<String, Snapshot>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(serde_json::from_str(&contents)?),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(err) => Err(err),
+    }
+}
+
+/// A platform and the URL of the link on it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PlatformLink {
+    pub platform: Platform,
+    pub url: String,
+}
+
+/// The platforms a watched link appeared on or disappeared from, printed and
+/// sent to the webhook.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    /// The watched link, as given to `watch`.
+    pub input: String,
+    /// The title and artist of the entity.
+    pub summary: String,
+    pub entity_unique_id: String,
+    pub page_url: String,
+    /// When the change was seen, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub added: Vec<PlatformLink>,
+    pub removed: Vec<PlatformLink>,
+}
+
+impl Notification {
+    /// The platforms added or removed going from `old` to `new`, `None` if
+    /// there are none.
+    pub fn new(input: &str, old: &LinksAPIResult, new: &LinksAPIResult) -> Option<Self> {
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+        for change in odesli_rs::diff(old, new).changes {
+            match change {
+                Change::PlatformAdded { platform, link } => {
+                    added.push(PlatformLink { platform, url: link.url })
+                }
+                Change::PlatformRemoved { platform, link } => {
+                    removed.push(PlatformLink { platform, url: link.url })
+                }
+                _ => {}
+            }
+        }
+        if added.is_empty() && removed.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            input: input.to_string(),
+            summary: crate::history::summarize(new),
+            entity_unique_id: new.entity_unique_id.clone(),
+            page_url: new.page_url.clone(),
+            timestamp: now().as_secs(),
+            added,
+            removed,
+        })
+    }
+
+    /// POST this notification as JSON to `webhook`.
+    pub async fn send(&self, client: &reqwest::Client, webhook: &str) -> reqwest::Result<()> {
+        client.post(webhook).json(self).send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ResultBuilder;
+
+    /// A result for Sugar with links on the `platforms`.
+    fn result(platforms: &[Platform]) -> LinksAPIResult {
+        let builder =
+            ResultBuilder::new("SPOTIFY_SONG::1").entity("SPOTIFY_SONG::1", "Sugar", "Maroon 5");
+        platforms
+            .iter()
+            .fold(builder, |builder, platform| {
+                let url = format!("https://{}.example/1", platform.as_str());
+                builder.link(platform.clone(), "SPOTIFY_SONG::1", &url)
+            })
+            .build()
+    }
+
+    fn platforms(links: &[PlatformLink]) -> Vec<Platform> {
+        links.iter().map(|link| link.platform.clone()).collect()
+    }
+
+    #[test]
+    fn notifications_list_added_and_removed_platforms() {
+        let old = result(&[Platform::Spotify, Platform::Deezer]);
+        let new = result(&[Platform::Spotify, Platform::Tidal, Platform::AppleMusic]);
+        let notification = Notification::new("spotify:track:1", &old, &new).unwrap();
+        assert_eq!(notification.input, "spotify:track:1");
+        assert_eq!(notification.summary, "Sugar by Maroon 5");
+        assert_eq!(notification.page_url, "https://song.link/s/1");
+        assert_eq!(platforms(&notification.added), [Platform::AppleMusic, Platform::Tidal]);
+        assert_eq!(notification.added[1].url, "https://tidal.example/1");
+        assert_eq!(platforms(&notification.removed), [Platform::Deezer]);
+    }
+
+    #[test]
+    fn no_notification_without_platform_changes() {
+        let old = result(&[Platform::Spotify, Platform::Deezer]);
+        assert!(Notification::new("x", &old, &old).is_none());
+
+        // Other changes, like a new URL on a platform, are not notified.
+        let mut new = old.clone();
+        new.links_by_platform.get_mut(&Platform::Deezer).unwrap().url = "https://d/2".to_string();
+        new.page_url = "https://song.link/s/2".to_string();
+        assert!(Notification::new("x", &old, &new).is_none());
+    }
+
+    #[test]
+    fn saving_keeps_the_links_of_other_processes() {
+        let dir = std::env::temp_dir().join(format!("odesli-watch-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("watch.json");
+
+        let mut first = WatchState::load(path.clone()).unwrap();
+        let mut second = WatchState::load(path.clone()).unwrap();
+        first.update("a", result(&[Platform::Spotify]), Vec::new());
+        first.save().unwrap();
+        second.update("b", result(&[Platform::Deezer]), Vec::new());
+        second.save().unwrap();
+        // `first` saves its own links only, not its stale view of `b`.
+        let (old, new) =
+            (result(&[Platform::Spotify]), result(&[Platform::Spotify, Platform::Tidal]));
+        let pending = Notification::new("a", &old, &new).unwrap();
+        first.update("a", new, vec![pending]);
+        first.save().unwrap();
+
+        let state = WatchState::load(path).unwrap();
+        let links = |input| state.get(input).unwrap().result.links_by_platform.len();
+        assert_eq!((links("a"), links("b")), (2, 1));
+        assert!(first.get("b").is_some());
+        // The notifications not delivered yet are kept for the next check.
+        let pending = &state.get("a").unwrap().pending;
+        assert_eq!(platforms(&pending[0].added), [Platform::Tidal]);
+        assert!(state.get("b").unwrap().pending.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,119 @@
+//! Resuming a batch must not list the retried inputs twice in its output.
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Serve results for every URL, failing those containing `flaky` with a 500
+/// while `failing` is set.
+fn serve(failing: Arc<AtomicBool>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            // Skip the headers, there is no body.
+            let mut header = String::new();
+            while reader.read_line(&mut header).unwrap() > 2 {
+                header.clear();
+            }
+
+            let (status, body) = if request_line.contains("flaky") && failing.load(Ordering::SeqCst)
+            {
+                ("500 Internal Server Error", "{}".to_string())
+            } else {
+                let id = if request_line.contains("flaky") { "flaky" } else { "steady" };
+                let body = format!(
+                    r#"{{"entityUniqueId":"SPOTIFY_SONG::{id}","userCountry":"US",
+                        "pageUrl":"https://song.link/s/{id}","linksByPlatform":{{}},
+                        "entitiesByUniqueId":{{}}}}"#
+                );
+                ("200 OK", body)
+            };
+            write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        }
+    });
+    base_url
+}
+
+/// A directory of its own for `test`, emptied.
+fn test_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("odesli-{}-{}", test, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run_batch(dir: &Path, resume: bool) -> std::process::Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_odesli"));
+    command
+        .args(["--config", dir.join("config.toml").to_str().unwrap()])
+        .args(["--no-history", "--no-catalog", "batch", "--rate-limit", "0"])
+        .args(["--output", dir.join("results.ndjson").to_str().unwrap()])
+        .arg(dir.join("links.txt"))
+        .env("XDG_DATA_HOME", dir)
+        .env_remove("ODESLI_API_KEY");
+    if resume {
+        command.arg("--resume");
+    }
+    command.output().unwrap()
+}
+
+fn output_inputs(dir: &Path) -> Vec<(String, bool)> {
+    let mut contents = String::new();
+    std::fs::File::open(dir.join("results.ndjson")).unwrap().read_to_string(&mut contents).unwrap();
+    contents
+        .lines()
+        .map(|line| {
+            let entry = serde_json::from_str::<serde_json::Value>(line).unwrap();
+            (entry["input"].as_str().unwrap().to_string(), entry.get("result").is_some())
+        })
+        .collect()
+}
+
+#[test]
+fn resume_lists_retried_inputs_once() {
+    let failing = Arc::new(AtomicBool::new(true));
+    let base_url = serve(failing.clone());
+    let dir = test_dir("resume");
+    std::fs::write(dir.join("config.toml"), format!("base_url = \"{base_url}\"\n")).unwrap();
+    std::fs::write(
+        dir.join("links.txt"),
+        "https://open.spotify.com/track/steady\nhttps://open.spotify.com/track/flaky\n",
+    )
+    .unwrap();
+
+    let first = run_batch(&dir, false);
+    assert!(first.status.success(), "{first:?}");
+    let stderr = String::from_utf8_lossy(&first.stderr);
+    assert!(stderr.contains("track/flaky"), "{stderr}");
+    assert_eq!(output_inputs(&dir), [("https://open.spotify.com/track/steady".to_string(), true)]);
+
+    failing.store(false, Ordering::SeqCst);
+    let second = run_batch(&dir, true);
+    assert!(second.status.success(), "{second:?}");
+    assert_eq!(
+        output_inputs(&dir),
+        [
+            ("https://open.spotify.com/track/steady".to_string(), true),
+            ("https://open.spotify.com/track/flaky".to_string(), true),
+        ]
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
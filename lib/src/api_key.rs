@@ -0,0 +1,164 @@
+use std::{path::Path, process::Stdio};
+
+use crate::OdesliError;
+
+/// An Odesli API key. Its `Debug` output is redacted, so that it cannot end up
+/// in logs by accident.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    /// Wrap `key`, which is then redacted from the `Debug` output. Use
+    /// [`Self::expose`] to read it back.
+    pub fn new(key: String) -> Self {
+        Self(key)
+    }
+
+    /// The key itself, to send it to the API.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for ApiKey {
+    fn from(key: String) -> Self {
+        Self(key)
+    }
+}
+
+impl From<&str> for ApiKey {
+    fn from(key: &str) -> Self {
+        Self(key.to_string())
+    }
+}
+
+impl std::fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ApiKey(<redacted>)")
+    }
+}
+
+/// The value replacing the API key in the URLs of errors.
+pub const REDACTED: &str = "REDACTED";
+
+/// Replace the value of the `key` query parameter of `url`, if any, with
+/// [`REDACTED`].
+pub fn redact_url(url: &mut reqwest::Url) {
+    if !url.query_pairs().any(|(name, _)| name == "key") {
+        return;
+    }
+    let pairs = url
+        .query_pairs()
+        .map(|(name, value)| {
+            let value = if name == "key" { REDACTED.into() } else { value };
+            (name.into_owned(), value.into_owned())
+        })
+        .collect::<Vec<_>>();
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+}
+
+/// Scrub the API key from the URL `error` carries, which is part of its
+/// `Display` and `Debug` output.
+pub(crate) fn redact_error(mut error: reqwest::Error) -> reqwest::Error {
+    if let Some(url) = error.url_mut() {
+        redact_url(url);
+    }
+    error
+}
+
+/// Environment variable holding the API key itself.
+pub const API_KEY_ENV: &str = "ODESLI_API_KEY";
+/// Environment variable holding the path of a file containing the API key.
+pub const API_KEY_FILE_ENV: &str = "ODESLI_API_KEY_FILE";
+/// Environment variable holding a shell command printing the API key, e.g.
+/// `pass show odesli`.
+pub const API_KEY_COMMAND_ENV: &str = "ODESLI_API_KEY_COMMAND";
+
+/// Get the API key from the environment: from [`API_KEY_ENV`], else from the
+/// file at [`API_KEY_FILE_ENV`], else from the output of
+/// [`API_KEY_COMMAND_ENV`]. Empty variables are ignored.
+///
+/// Returns `Ok(None)` if none of them is set.
+pub fn api_key_from_env() -> Result<Option<ApiKey>, OdesliError> {
+    if let Some(key) = non_empty_env(API_KEY_ENV) {
+        return Ok(Some(ApiKey(key)));
+    }
+    if let Some(path) = non_empty_env(API_KEY_FILE_ENV) {
+        return read_api_key_file(Path::new(&path)).map(Some);
+    }
+    if let Some(command) = non_empty_env(API_KEY_COMMAND_ENV) {
+        return api_key_from_command(&command).map(Some);
+    }
+    Ok(None)
+}
+
+fn non_empty_env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.trim().is_empty())
+}
+
+/// Read the API key from the file at `path`, ignoring surrounding whitespace.
+///
+/// On Unix, the file must not be accessible by the group or other users (as
+/// with `chmod 600`).
+pub fn read_api_key_file(path: &Path) -> Result<ApiKey, OdesliError> {
+    let error = |message: String| {
+        OdesliError::ApiKeySourceError(format!("key file '{}' {}", path.display(), message))
+    };
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let metadata =
+            std::fs::metadata(path).map_err(|err| error(format!("is unreadable: {err}")))?;
+        let mode = metadata.permissions().mode() & 0o777;
+        if mode & 0o077 != 0 {
+            return Err(error(format!(
+                "is accessible by other users (mode {mode:o}), restrict it with `chmod 600`"
+            )));
+        }
+    }
+
+    let key =
+        std::fs::read_to_string(path).map_err(|err| error(format!("is unreadable: {err}")))?;
+    non_empty_key(key).ok_or_else(|| error("is empty".to_string()))
+}
+
+/// Run `command` with the shell and use what it prints as the API key,
+/// ignoring surrounding whitespace.
+///
+/// The command can still prompt the user, its stdin and stderr are those of
+/// the current process.
+pub fn api_key_from_command(command: &str) -> Result<ApiKey, OdesliError> {
+    let error = |message: String| {
+        OdesliError::ApiKeySourceError(format!("key command '{command}' {message}"))
+    };
+
+    #[cfg(windows)]
+    let mut shell = std::process::Command::new("cmd");
+    #[cfg(windows)]
+    shell.arg("/C");
+    #[cfg(not(windows))]
+    let mut shell = std::process::Command::new("sh");
+    #[cfg(not(windows))]
+    shell.arg("-c");
+
+    let output = shell
+        .arg(command)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|err| error(format!("could not be run: {err}")))?;
+    if !output.status.success() {
+        return Err(error(format!("failed with {}", output.status)));
+    }
+
+    let key =
+        String::from_utf8(output.stdout).map_err(|_| error("printed invalid UTF-8".to_string()))?;
+    non_empty_key(key).ok_or_else(|| error("printed nothing".to_string()))
+}
+
+fn non_empty_key(key: String) -> Option<ApiKey> {
+    let key = key.trim();
+    (!key.is_empty()).then(|| ApiKey(key.to_string()))
+}
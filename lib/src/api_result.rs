@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use crate::{APIProvider, EntityType, Platform};
 
@@ -87,15 +87,17 @@ pub struct LinksAPIResult {
     pub page_url: String,
 
     /// Each key is a platform, and each value is a struct that contains data
-    /// for linking to the match.
+    /// for linking to the match. Platforms are sorted in the order of
+    /// [`Platform`].
     #[serde(rename = "linksByPlatform")]
-    pub links_by_platform: HashMap<Platform, Link>,
+    pub links_by_platform: BTreeMap<Platform, Link>,
 
     /// Each key is a unique identifier for a streaming entity, and each value
     /// is an object that contains data for that entity, such as `title`,
-    /// `artist_name`, `thumbnail_url`, etc.
+    /// `artist_name`, `thumbnail_url`, etc. Unique identifiers are sorted
+    /// alphabetically.
     #[serde(rename = "entitiesByUniqueId")]
-    pub entities_by_unique_id: HashMap<String, Entity>,
+    pub entities_by_unique_id: BTreeMap<String, Entity>,
 }
 
 impl LinksAPIResult {
@@ -110,4 +112,209 @@ impl LinksAPIResult {
             None
         }
     }
+
+    /// Pick the thumbnail that best fits the requested size among all the
+    /// entities in this result.
+    ///
+    /// The size of a thumbnail is the larger of its width and height. Among
+    /// the thumbnails whose size falls within `min..=max`, the largest one is
+    /// chosen. If none fit, the one closest to that range is chosen instead.
+    /// Thumbnails without known dimensions are only returned when no sized
+    /// thumbnail is available. `None` is returned if `min` is larger than
+    /// `max`, as no size could fit.
+    ///
+    /// # Arguments
+    ///
+    /// * `min`: The minimum acceptable size (in pixels).
+    /// * `max`: The maximum acceptable size (in pixels).
+    pub fn best_thumbnail(&self, min: u64, max: u64) -> Option<Thumbnail<'_>> {
+        if min > max {
+            return None;
+        }
+
+        let thumbnails = self
+            .entities_by_unique_id
+            .iter()
+            .filter_map(|(unique_id, entity)| Thumbnail::from_entity(unique_id, entity))
+            .collect::<Vec<_>>();
+
+        let sized = thumbnails.iter().filter_map(|thumbnail| Some((thumbnail.size()?, thumbnail)));
+
+        let within_range = sized
+            .clone()
+            .filter(|(size, _)| (min..=max).contains(size))
+            .max_by_key(|(size, _)| *size)
+            .map(|(_, thumbnail)| thumbnail);
+        if let Some(thumbnail) = within_range {
+            return Some(thumbnail.clone());
+        }
+
+        let closest = sized
+            .min_by_key(|(size, _)| {
+                let distance = if *size < min { min - size } else { size.saturating_sub(max) };
+                // On equal distance, prefer the larger image.
+                (distance, std::cmp::Reverse(*size))
+            })
+            .map(|(_, thumbnail)| thumbnail);
+        if let Some(thumbnail) = closest {
+            return Some(thumbnail.clone());
+        }
+
+        thumbnails.into_iter().next()
+    }
+}
+
+/// A thumbnail of an [`Entity`], as returned by
+/// [`LinksAPIResult::best_thumbnail`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Thumbnail<'a> {
+    /// The unique ID of the entity this thumbnail belongs to.
+    pub entity_unique_id: &'a str,
+    /// The URL of the thumbnail.
+    pub url: &'a str,
+    /// Width of the thumbnail, if known.
+    pub width: Option<u64>,
+    /// Height of the thumbnail, if known.
+    pub height: Option<u64>,
+}
+
+impl<'a> Thumbnail<'a> {
+    fn from_entity(entity_unique_id: &'a str, entity: &'a Entity) -> Option<Self> {
+        Some(Self {
+            entity_unique_id,
+            url: entity.thumbnail_url.as_deref()?,
+            width: entity.thumbnail_width,
+            height: entity.thumbnail_height,
+        })
+    }
+
+    /// The larger of the width and height, if both are known.
+    pub fn size(&self) -> Option<u64> {
+        Some(self.width?.max(self.height?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A result with an entity per `(unique_id, thumbnail size)`, the
+    /// thumbnails being square.
+    fn result_with_thumbnails(thumbnails: &[(&str, Option<u64>)]) -> LinksAPIResult {
+        let entities = thumbnails
+            .iter()
+            .map(|(unique_id, size)| {
+                let entity = serde_json::json!({
+                    "id": unique_id,
+                    "type": "song",
+                    "thumbnailUrl": format!("https://img.example/{unique_id}.jpg"),
+                    "thumbnailWidth": size,
+                    "thumbnailHeight": size,
+                    "apiProvider": "spotify",
+                    "platforms": ["spotify"],
+                });
+                (unique_id.to_string(), entity)
+            })
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::from_value(serde_json::json!({
+            "entityUniqueId": thumbnails[0].0,
+            "userCountry": "US",
+            "pageUrl": "https://song.link/s/1",
+            "linksByPlatform": {},
+            "entitiesByUniqueId": entities,
+        }))
+        .unwrap()
+    }
+
+    fn best(result: &LinksAPIResult, min: u64, max: u64) -> Option<&str> {
+        result.best_thumbnail(min, max).map(|thumbnail| thumbnail.entity_unique_id)
+    }
+
+    #[test]
+    fn best_thumbnail_prefers_the_largest_within_range() {
+        let result =
+            result_with_thumbnails(&[("A", Some(64)), ("B", Some(300)), ("C", Some(640))]);
+        assert_eq!(best(&result, 100, 640), Some("C"));
+        assert_eq!(best(&result, 100, 500), Some("B"));
+    }
+
+    #[test]
+    fn best_thumbnail_falls_back_to_the_closest_size() {
+        let result = result_with_thumbnails(&[("A", Some(64)), ("B", Some(1000))]);
+        assert_eq!(best(&result, 100, 200), Some("A"));
+        assert_eq!(best(&result, 700, 800), Some("B"));
+        // On equal distance, the larger image wins.
+        let result = result_with_thumbnails(&[("A", Some(100)), ("B", Some(300))]);
+        assert_eq!(best(&result, 150, 250), Some("B"));
+    }
+
+    #[test]
+    fn best_thumbnail_uses_unsized_thumbnails_last() {
+        let result = result_with_thumbnails(&[("A", None), ("B", Some(2000))]);
+        assert_eq!(best(&result, 100, 200), Some("B"));
+        let result = result_with_thumbnails(&[("B", None), ("A", None)]);
+        assert_eq!(best(&result, 100, 200), Some("A"));
+    }
+
+    #[test]
+    fn best_thumbnail_rejects_an_empty_range() {
+        let result = result_with_thumbnails(&[("A", Some(300))]);
+        assert_eq!(best(&result, 400, 200), None);
+    }
+
+    /// A result with links for `platforms` and an entity per ID of `entities`,
+    /// given to the deserializer in these orders.
+    fn result_in_order(platforms: &[&str], entities: &[&str]) -> LinksAPIResult {
+        let link = |platform: &&str| {
+            let link = serde_json::json!({
+                "entityUniqueId": "SPOTIFY_SONG::1",
+                "url": format!("https://{platform}.example/1"),
+            });
+            (platform.to_string(), link)
+        };
+        let entity = |unique_id: &&str| {
+            let entity = serde_json::json!({
+                "id": unique_id,
+                "type": "song",
+                "apiProvider": "spotify",
+                "platforms": ["spotify"],
+            });
+            (unique_id.to_string(), entity)
+        };
+        serde_json::from_value(serde_json::json!({
+            "entityUniqueId": "SPOTIFY_SONG::1",
+            "userCountry": "US",
+            "pageUrl": "https://song.link/s/1",
+            "linksByPlatform": platforms.iter().map(link).collect::<serde_json::Map<_, _>>(),
+            "entitiesByUniqueId": entities.iter().map(entity).collect::<serde_json::Map<_, _>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn serialization_is_reproducible() {
+        let platforms = ["bandcamp", "deezer", "spotify", "youtube", "appleMusic"];
+        let entities = ["SPOTIFY_SONG::1", "DEEZER_SONG::2", "BANDCAMP_SONG::3"];
+        let json = serde_json::to_string(&result_in_order(&platforms, &entities)).unwrap();
+
+        let mut reversed_platforms = platforms;
+        reversed_platforms.reverse();
+        let mut reversed_entities = entities;
+        reversed_entities.reverse();
+        let reversed = result_in_order(&reversed_platforms, &reversed_entities);
+        assert_eq!(serde_json::to_string(&reversed).unwrap(), json);
+        let reparsed = serde_json::from_str::<LinksAPIResult>(&json).unwrap();
+        assert_eq!(serde_json::to_string(&reparsed).unwrap(), json);
+
+        // Links follow the declaration order of Platform, entities their IDs.
+        let positions = |keys: &[&str]| {
+            keys.iter()
+                .map(|key| json.find(&format!("\"{key}\":{{")).expect("key is serialized"))
+                .collect::<Vec<_>>()
+        };
+        let links = positions(&["spotify", "appleMusic", "youtube", "deezer", "bandcamp"]);
+        assert!(links.is_sorted(), "{json}");
+        let entities = positions(&["BANDCAMP_SONG::3", "DEEZER_SONG::2", "SPOTIFY_SONG::1"]);
+        assert!(entities.is_sorted(), "{json}");
+    }
 }
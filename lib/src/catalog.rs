@@ -0,0 +1,556 @@
+use std::{
+    path::Path,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{EntityUniqueId, LinksAPIResult, OdesliError, Platform, SearchHit, SearchIndex};
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS results (
+    entity_unique_id TEXT NOT NULL,
+    user_country TEXT NOT NULL,
+    result TEXT NOT NULL,
+    recorded_at INTEGER NOT NULL,
+    PRIMARY KEY (entity_unique_id, user_country)
+);
+CREATE TABLE IF NOT EXISTS entities (
+    unique_id TEXT PRIMARY KEY,
+    title TEXT,
+    artist_name TEXT,
+    entity TEXT NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS equivalences (
+    source_id TEXT NOT NULL,
+    target_id TEXT NOT NULL,
+    user_country TEXT NOT NULL,
+    recorded_at INTEGER NOT NULL,
+    PRIMARY KEY (source_id, target_id, user_country)
+);
+CREATE INDEX IF NOT EXISTS equivalences_by_target ON equivalences (target_id);
+CREATE TABLE IF NOT EXISTS counters (
+    name TEXT PRIMARY KEY,
+    value INTEGER NOT NULL
+);
+";
+
+/// An entity known to be the same as the one looked up in the [`Catalog`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Equivalent {
+    /// The unique ID of the equivalent entity.
+    pub unique_id: EntityUniqueId,
+    /// The `user_country` of the lookup that linked both entities.
+    pub user_country: String,
+    /// When the link between both entities was last recorded.
+    pub recorded_at: SystemTime,
+}
+
+/// A result stored in the [`Catalog`], see [`Catalog::results`].
+#[derive(Clone, Debug)]
+pub struct CachedResult {
+    /// When the result was recorded.
+    pub recorded_at: SystemTime,
+    pub result: LinksAPIResult,
+}
+
+/// Figures about the results stored in a [`Catalog`], see [`Catalog::stats`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CatalogStats {
+    /// The number of results stored.
+    pub results: u64,
+    /// The size of the database, in bytes.
+    pub size: u64,
+    /// When the oldest result was recorded, if any.
+    pub oldest: Option<SystemTime>,
+    /// When the newest result was recorded, if any.
+    pub newest: Option<SystemTime>,
+    /// The number of [`Catalog::lookup`]s that found a result.
+    pub hits: u64,
+    /// The number of [`Catalog::lookup`]s that found none.
+    pub misses: u64,
+}
+
+impl CatalogStats {
+    /// The share of the lookups that found a result, `None` before the first
+    /// lookup.
+    pub fn hit_ratio(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
+}
+
+/// A local SQLite catalog of the mappings between platform IDs learned from
+/// previous lookups.
+///
+/// Every recorded [`LinksAPIResult`] is stored as equivalence edges going
+/// from its input entity to all the other entities in it, along with the time
+/// and country of the lookup. The result itself is kept too, so that it can be
+/// served again without calling the API (see
+/// [`crate::ClientBuilder::with_catalog`]).
+///
+/// Cloning a catalog is cheap, all the clones share the same connection.
+#[derive(Clone)]
+pub struct Catalog {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl Catalog {
+    /// Open (or create) the catalog stored at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, OdesliError> {
+        Self::from_connection(Connection::open(path).map_err(OdesliError::CatalogError)?)
+    }
+
+    /// Create a catalog that only lives in memory.
+    pub fn open_in_memory() -> Result<Self, OdesliError> {
+        Self::from_connection(Connection::open_in_memory().map_err(OdesliError::CatalogError)?)
+    }
+
+    fn from_connection(connection: Connection) -> Result<Self, OdesliError> {
+        connection.execute_batch(SCHEMA).map_err(OdesliError::CatalogError)?;
+        Ok(Self { connection: Arc::new(Mutex::new(connection)) })
+    }
+
+    /// Run `f` with the underlying connection.
+    pub(crate) fn with_connection<T>(
+        &self,
+        f: impl FnOnce(&mut Connection) -> rusqlite::Result<T>,
+    ) -> Result<T, OdesliError> {
+        let mut connection = self.connection.lock().unwrap_or_else(|err| err.into_inner());
+        f(&mut connection).map_err(OdesliError::CatalogError)
+    }
+
+    /// Record a result in the catalog, replacing any previous result for the
+    /// same input entity and country.
+    pub fn record(&self, result: &LinksAPIResult) -> Result<(), OdesliError> {
+        self.record_at(result, SystemTime::now())
+    }
+
+    /// Record a result exported from another catalog, unless a result for the
+    /// same input entity and country was recorded after it.
+    pub fn import(&self, cached: &CachedResult) -> Result<(), OdesliError> {
+        self.record_at(&cached.result, cached.recorded_at)
+    }
+
+    /// Record `result` as of `recorded_at`, keeping the rows recorded later.
+    fn record_at(
+        &self,
+        result: &LinksAPIResult,
+        recorded_at: SystemTime,
+    ) -> Result<(), OdesliError> {
+        let recorded_at = unix_timestamp(recorded_at);
+        let result_json = serde_json::to_string(result).expect("result is serializable");
+        let entities = result
+            .entities_by_unique_id
+            .iter()
+            .map(|(unique_id, entity)| {
+                (unique_id, entity, serde_json::to_string(entity).expect("entity is serializable"))
+            })
+            .collect::<Vec<_>>();
+
+        self.with_connection(|connection| {
+            let transaction = connection.transaction()?;
+
+            transaction.execute(
+                "INSERT INTO results (entity_unique_id, user_country, result, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (entity_unique_id, user_country) DO UPDATE
+                 SET result = excluded.result, recorded_at = excluded.recorded_at
+                 WHERE excluded.recorded_at >= results.recorded_at",
+                params![result.entity_unique_id, result.user_country, result_json, recorded_at],
+            )?;
+
+            for (unique_id, entity, entity_json) in entities.iter() {
+                transaction.execute(
+                    "INSERT INTO entities (unique_id, title, artist_name, entity, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)
+                     ON CONFLICT (unique_id) DO UPDATE
+                     SET title = excluded.title, artist_name = excluded.artist_name,
+                         entity = excluded.entity, updated_at = excluded.updated_at
+                     WHERE excluded.updated_at >= entities.updated_at",
+                    params![unique_id, entity.title, entity.artist_name, entity_json, recorded_at],
+                )?;
+
+                if **unique_id != result.entity_unique_id {
+                    transaction.execute(
+                        "INSERT INTO equivalences (source_id, target_id, user_country, recorded_at)
+                         VALUES (?1, ?2, ?3, ?4)
+                         ON CONFLICT (source_id, target_id, user_country) DO UPDATE
+                         SET recorded_at = MAX(recorded_at, excluded.recorded_at)",
+                        params![result.entity_unique_id, unique_id, result.user_country, recorded_at],
+                    )?;
+                }
+            }
+
+            transaction.commit()
+        })
+    }
+
+    /// All the entities known to be the same as `unique_id`, most recently
+    /// recorded first.
+    pub fn equivalents(&self, unique_id: &EntityUniqueId) -> Result<Vec<Equivalent>, OdesliError> {
+        let rows = self.with_connection(|connection| {
+            // Edges only go from the input entity of a lookup to the other
+            // entities in it, so the entity may be on either end of an edge,
+            // or be linked to its siblings through a shared input entity.
+            let mut statement = connection.prepare(
+                "SELECT id, user_country, MAX(recorded_at) AS recorded_at FROM (
+                     SELECT target_id AS id, user_country, recorded_at
+                     FROM equivalences WHERE source_id = ?1
+                     UNION ALL
+                     SELECT source_id AS id, user_country, recorded_at
+                     FROM equivalences WHERE target_id = ?1
+                     UNION ALL
+                     SELECT sibling.target_id AS id, sibling.user_country, sibling.recorded_at
+                     FROM equivalences AS edge
+                     JOIN equivalences AS sibling
+                         ON sibling.source_id = edge.source_id
+                         AND sibling.user_country = edge.user_country
+                     WHERE edge.target_id = ?1
+                 )
+                 WHERE id != ?1
+                 GROUP BY id, user_country
+                 ORDER BY recorded_at DESC, id",
+            )?;
+            let rows = statement
+                .query_map(params![unique_id.to_string()], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id, user_country, recorded_at)| {
+                Some(Equivalent {
+                    unique_id: EntityUniqueId::from_str(&id).ok()?,
+                    user_country,
+                    recorded_at: from_unix_timestamp(recorded_at),
+                })
+            })
+            .collect())
+    }
+
+    /// The most recently recorded entity on `platform` that is the same as
+    /// `unique_id`, e.g. the Tidal ID for a Spotify ID.
+    pub fn find_equivalent(
+        &self,
+        unique_id: &EntityUniqueId,
+        platform: &Platform,
+    ) -> Result<Option<EntityUniqueId>, OdesliError> {
+        let provider = platform.api_provider();
+        Ok(self
+            .equivalents(unique_id)?
+            .into_iter()
+            .map(|equivalent| equivalent.unique_id)
+            .find(|equivalent| equivalent.provider == provider))
+    }
+
+    /// The most recently recorded result that contains `unique_id`, counted
+    /// as a hit or a miss in the [`CatalogStats`].
+    ///
+    /// # Arguments
+    ///
+    /// * `unique_id`: The entity to look for.
+    /// * `user_country`: Only consider results for this country, if given.
+    /// * `max_age`: Ignore results recorded longer ago than this, if given.
+    pub fn lookup(
+        &self,
+        unique_id: &EntityUniqueId,
+        user_country: Option<&str>,
+        max_age: Option<Duration>,
+    ) -> Result<Option<LinksAPIResult>, OdesliError> {
+        let result = self.find(unique_id, user_country, max_age.map_or(i64::MIN, cutoff))?;
+        self.with_connection(|connection| {
+            connection.execute(
+                "INSERT INTO counters (name, value) VALUES (?1, 1)
+                 ON CONFLICT (name) DO UPDATE SET value = value + 1",
+                params![if result.is_some() { "hits" } else { "misses" }],
+            )
+        })?;
+
+        result.map(parse_result).transpose()
+    }
+
+    /// The most recently recorded result that contains `unique_id`, in any
+    /// country. Unlike [`Self::lookup`], this is not counted in the
+    /// [`CatalogStats`].
+    pub fn result_for(
+        &self,
+        unique_id: &EntityUniqueId,
+    ) -> Result<Option<LinksAPIResult>, OdesliError> {
+        self.find(unique_id, None, i64::MIN)?.map(parse_result).transpose()
+    }
+
+    /// The JSON of the most recently recorded result that contains
+    /// `unique_id`, for `user_country` if given, recorded at `recorded_after`
+    /// or later.
+    fn find(
+        &self,
+        unique_id: &EntityUniqueId,
+        user_country: Option<&str>,
+        recorded_after: i64,
+    ) -> Result<Option<String>, OdesliError> {
+        self.with_connection(|connection| {
+            connection
+                .query_row(
+                    "SELECT result FROM results
+                     WHERE (entity_unique_id = ?1
+                            OR entity_unique_id IN (
+                                SELECT source_id FROM equivalences
+                                WHERE target_id = ?1 AND user_country = results.user_country
+                            ))
+                         AND (?2 IS NULL OR user_country = ?2)
+                         AND recorded_at >= ?3
+                     ORDER BY recorded_at DESC
+                     LIMIT 1",
+                    params![unique_id.to_string(), user_country, recorded_after],
+                    |row| row.get::<_, String>(0),
+                )
+                .optional()
+        })
+    }
+
+    /// All the results stored in the catalog, most recently recorded first.
+    pub fn results(&self) -> Result<Vec<CachedResult>, OdesliError> {
+        let rows = self.with_connection(|connection| {
+            let mut statement = connection
+                .prepare("SELECT result, recorded_at FROM results ORDER BY recorded_at DESC")?;
+            let rows = statement
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })?;
+
+        rows.into_iter()
+            .map(|(result, recorded_at)| {
+                Ok(CachedResult {
+                    recorded_at: from_unix_timestamp(recorded_at),
+                    result: serde_json::from_str(&result).map_err(|err| {
+                        OdesliError::ParseError { error: err.to_string(), body: result.clone() }
+                    })?,
+                })
+            })
+            .collect()
+    }
+
+    /// Delete the results recorded longer ago than `max_age`, returning how
+    /// many were deleted. The IDs learned from them are kept.
+    pub fn purge(&self, max_age: Duration) -> Result<usize, OdesliError> {
+        let recorded_before = cutoff(max_age);
+        self.with_connection(|connection| {
+            connection
+                .execute("DELETE FROM results WHERE recorded_at < ?1", params![recorded_before])
+        })
+    }
+
+    /// Count the stored results, and the lookups that found one or not.
+    pub fn stats(&self) -> Result<CatalogStats, OdesliError> {
+        self.with_connection(|connection| {
+            let (results, oldest, newest) = connection.query_row(
+                "SELECT COUNT(*), MIN(recorded_at), MAX(recorded_at) FROM results",
+                [],
+                |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, Option<i64>>(1)?,
+                        row.get::<_, Option<i64>>(2)?,
+                    ))
+                },
+            )?;
+            let size = connection.query_row(
+                "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+                [],
+                |row| row.get::<_, i64>(0),
+            )?;
+            let counter = |name: &str| {
+                connection
+                    .query_row("SELECT value FROM counters WHERE name = ?1", params![name], |row| {
+                        row.get::<_, i64>(0)
+                    })
+                    .optional()
+                    .map(|value| value.unwrap_or_default() as u64)
+            };
+
+            Ok(CatalogStats {
+                results: results as u64,
+                size: size as u64,
+                oldest: oldest.map(from_unix_timestamp),
+                newest: newest.map(from_unix_timestamp),
+                hits: counter("hits")?,
+                misses: counter("misses")?,
+            })
+        })
+    }
+
+    /// Build a [`SearchIndex`] over all the entities in the catalog.
+    pub fn search_index(&self) -> Result<SearchIndex, OdesliError> {
+        self.with_connection(|connection| {
+            let mut statement =
+                connection.prepare("SELECT unique_id, title, artist_name FROM entities")?;
+            let mut index = SearchIndex::default();
+            for row in statement
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?)))?
+            {
+                let (unique_id, title, artist_name) = row?;
+                index.insert(unique_id, title, artist_name);
+            }
+            Ok(index)
+        })
+    }
+
+    /// Fuzzy search the titles and artist names of the entities in the
+    /// catalog. See [`SearchIndex::search`].
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>, OdesliError> {
+        Ok(self.search_index()?.search(query, limit))
+    }
+}
+
+/// The timestamp `max_age` ago, the earliest one if `max_age` goes back further
+/// than timestamps do, so that it is older than everything.
+fn cutoff(max_age: Duration) -> i64 {
+    i64::try_from(max_age.as_secs())
+        .ok()
+        .and_then(|max_age| unix_timestamp(SystemTime::now()).checked_sub(max_age))
+        .unwrap_or(i64::MIN)
+}
+
+pub(crate) fn unix_timestamp(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| i64::try_from(duration.as_secs()).unwrap_or(i64::MAX))
+        .unwrap_or_default()
+}
+
+pub(crate) fn from_unix_timestamp(timestamp: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(timestamp.max(0) as u64)
+}
+
+/// Parse a result stored as JSON.
+fn parse_result(result: String) -> Result<LinksAPIResult, OdesliError> {
+    serde_json::from_str(&result)
+        .map_err(|err| OdesliError::ParseError { error: err.to_string(), body: result })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ResultBuilder;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// A result for the Spotify song `id`, linked to the Deezer song
+    /// `DEEZER_SONG::<id>`, with `title`.
+    fn result(id: &str, title: &str) -> LinksAPIResult {
+        let spotify = format!("SPOTIFY_SONG::{id}");
+        let deezer = format!("DEEZER_SONG::{id}");
+        ResultBuilder::new(&spotify)
+            .link(Platform::Spotify, &spotify, &format!("https://open.spotify.com/track/{id}"))
+            .link(Platform::Deezer, &deezer, &format!("https://www.deezer.com/track/{id}"))
+            .entity(&spotify, title, "Maroon 5")
+            .entity(&deezer, title, "Maroon 5")
+            .build()
+    }
+
+    fn import(catalog: &Catalog, result: LinksAPIResult, age: Duration) {
+        catalog.import(&CachedResult { recorded_at: SystemTime::now() - age, result }).unwrap();
+    }
+
+    fn titles(catalog: &Catalog) -> Vec<String> {
+        catalog
+            .results()
+            .unwrap()
+            .into_iter()
+            .map(|cached| {
+                let entity = &cached.result.entities_by_unique_id[&cached.result.entity_unique_id];
+                entity.title.clone().unwrap_or_default()
+            })
+            .collect()
+    }
+
+    fn unique_id(id: &str) -> EntityUniqueId {
+        EntityUniqueId::from_str(id).unwrap()
+    }
+
+    #[test]
+    fn lookups_follow_equivalences_and_max_age() {
+        let catalog = Catalog::open_in_memory().unwrap();
+        import(&catalog, result("1", "Sugar"), 2 * DAY);
+
+        let lookup = |id: &str, max_age| {
+            catalog.lookup(&unique_id(id), None, max_age).unwrap().map(|found| found.page_url)
+        };
+        let found = Some("https://song.link/s/1".to_string());
+        assert_eq!(lookup("SPOTIFY_SONG::1", None), found);
+        assert_eq!(lookup("DEEZER_SONG::1", None), found);
+        assert_eq!(lookup("DEEZER_SONG::1", Some(3 * DAY)), found);
+        assert_eq!(lookup("DEEZER_SONG::1", Some(DAY)), None);
+        assert_eq!(lookup("DEEZER_SONG::2", None), None);
+        // An age beyond the range of timestamps is older than everything.
+        assert_eq!(lookup("SPOTIFY_SONG::1", Some(Duration::MAX)), found);
+
+        let other_country = catalog.lookup(&unique_id("SPOTIFY_SONG::1"), Some("GB"), None);
+        assert!(other_country.unwrap().is_none());
+    }
+
+    #[test]
+    fn purge_deletes_the_old_results() {
+        let catalog = Catalog::open_in_memory().unwrap();
+        catalog.record(&result("1", "Sugar")).unwrap();
+        import(&catalog, result("2", "Memories"), 2 * DAY);
+        import(&catalog, result("3", "Animals"), 10 * DAY);
+
+        assert_eq!(catalog.purge(Duration::MAX).unwrap(), 0);
+        assert_eq!(catalog.purge(Duration::from_secs(u64::MAX / 2)).unwrap(), 0);
+        assert_eq!(catalog.purge(5 * DAY).unwrap(), 1);
+        assert_eq!(titles(&catalog), ["Sugar", "Memories"]);
+        assert_eq!(catalog.purge(DAY).unwrap(), 1);
+        assert_eq!(titles(&catalog), ["Sugar"]);
+
+        // The IDs learned from the purged results are kept.
+        let equivalent = catalog.find_equivalent(&unique_id("SPOTIFY_SONG::3"), &Platform::Deezer);
+        assert_eq!(equivalent.unwrap(), Some(unique_id("DEEZER_SONG::3")));
+    }
+
+    #[test]
+    fn imports_keep_the_newer_result() {
+        let catalog = Catalog::open_in_memory().unwrap();
+        import(&catalog, result("1", "Sugar (remastered)"), DAY);
+        import(&catalog, result("1", "Sugar"), 2 * DAY);
+        assert_eq!(titles(&catalog), ["Sugar (remastered)"]);
+        let search = catalog.search("sugar", 1).unwrap();
+        assert_eq!(search[0].title.as_deref(), Some("Sugar (remastered)"));
+
+        catalog.record(&result("1", "Sugar (live)")).unwrap();
+        assert_eq!(titles(&catalog), ["Sugar (live)"]);
+    }
+
+    #[test]
+    fn stats_count_results_and_lookups() {
+        let catalog = Catalog::open_in_memory().unwrap();
+        let stats = catalog.stats().unwrap();
+        assert_eq!((stats.results, stats.oldest, stats.newest), (0, None, None));
+        assert_eq!(stats.hit_ratio(), None);
+
+        import(&catalog, result("1", "Sugar"), 2 * DAY);
+        catalog.record(&result("2", "Memories")).unwrap();
+        for id in ["SPOTIFY_SONG::1", "DEEZER_SONG::2", "SPOTIFY_SONG::9"] {
+            catalog.lookup(&unique_id(id), None, None).unwrap();
+        }
+        catalog.lookup(&unique_id("SPOTIFY_SONG::1"), None, Some(DAY)).unwrap();
+        // Reading a result outside of a lookup is not counted.
+        assert!(catalog.result_for(&unique_id("DEEZER_SONG::1")).unwrap().is_some());
+        assert!(catalog.result_for(&unique_id("DEEZER_SONG::9")).unwrap().is_none());
+
+        let stats = catalog.stats().unwrap();
+        assert_eq!(stats.results, 2);
+        assert!(stats.size > 0);
+        let age = |time: Option<SystemTime>| time.unwrap().elapsed().unwrap().as_secs() / 3600;
+        assert_eq!((age(stats.oldest), age(stats.newest)), (48, 0));
+        assert_eq!((stats.hits, stats.misses), (2, 2));
+        assert_eq!(stats.hit_ratio(), Some(0.5));
+    }
+}
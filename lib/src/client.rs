@@ -1,19 +1,46 @@
+#[cfg(feature = "catalog")]
+use std::time::Duration;
+use std::time::Instant;
+
+use reqwest::Url;
+
+#[cfg(feature = "catalog")]
+use crate::{Catalog, EntityUniqueId};
 use crate::{
-    EntityType, LinksAPIResult, OdesliError, Platform, API_VERSION, BASE_URL, LINKS_ENDPOINT,
+    api_key::{redact_error, redact_url},
+    api_key_from_env, ApiKey, EntityType, InputKind, LinksAPIResult, OdesliError, ParsedInput,
+    Platform, API_VERSION, BASE_URL, LINKS_ENDPOINT,
 };
 
 /// Helper to build a client for Odesli. You can modify things like the `api_key`,
 /// `api_version` and the inner `http_client` using the builder.
 pub struct ClientBuilder {
-    api_key: Option<String>,
+    api_key: Option<ApiKey>,
     api_version: String,
+    base_url: String,
+    user_country: Option<String>,
     http_client: reqwest::Client,
+    #[cfg(feature = "catalog")]
+    catalog: Option<Catalog>,
+    #[cfg(feature = "catalog")]
+    catalog_lookup: Option<Option<Duration>>,
 }
 
 impl ClientBuilder {
+    /// A builder with the API key taken from the environment, see
+    /// [`crate::api_key_from_env`] for the variables read. Without any of them
+    /// set, this is the same as [`ClientBuilder::default`].
+    pub fn from_env() -> Result<Self, OdesliError> {
+        let builder = Self::default();
+        Ok(match api_key_from_env()? {
+            Some(key) => builder.with_api_key(key),
+            None => builder,
+        })
+    }
+
     /// Modify the Odesli API key being used in the API calls.
-    pub fn with_api_key(mut self, key: String) -> Self {
-        self.api_key = Some(key);
+    pub fn with_api_key(mut self, key: impl Into<ApiKey>) -> Self {
+        self.api_key = Some(key.into());
         self
     }
 
@@ -25,18 +52,63 @@ impl ClientBuilder {
         self
     }
 
+    /// Modify the URL of the API, e.g. to go through a proxy.
+    ///
+    /// Defaults to [`crate::BASE_URL`].
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Modify the country to get results for, as an ISO 3166-1 alpha-2 code
+    /// like `"US"`.
+    ///
+    /// Odesli uses `"US"` when none is given.
+    pub fn with_user_country(mut self, country: String) -> Self {
+        self.user_country = Some(country);
+        self
+    }
+
     /// Modify the [`reqwest::Client`] being used for making the calls.
     pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
         self.http_client = client;
         self
     }
 
+    /// Record every result received from the API in a [`crate::Catalog`].
+    ///
+    /// Failing to record a result does not fail the request.
+    #[cfg(feature = "catalog")]
+    pub fn with_catalog(mut self, catalog: Catalog) -> Self {
+        self.catalog = Some(catalog);
+        self
+    }
+
+    /// Consult the catalog set with [`Self::with_catalog`] before calling the
+    /// API in [`OdesliClient::get_by_id`], returning the stored result if one
+    /// contains the requested entity.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_age`: Ignore stored results older than this. `None` accepts
+    ///   results of any age.
+    #[cfg(feature = "catalog")]
+    pub fn with_catalog_lookup(mut self, max_age: Option<Duration>) -> Self {
+        self.catalog_lookup = Some(max_age);
+        self
+    }
+
     /// Build and return the [`crate::OdesliClient`] with the configuration set.
     pub fn build(self) -> OdesliClient {
         OdesliClient {
             api_key: self.api_key,
-            api_url: format!("{}/{}", BASE_URL, self.api_version),
+            api_url: format!("{}/{}", self.base_url, self.api_version),
+            user_country: self.user_country,
             http_client: self.http_client,
+            #[cfg(feature = "catalog")]
+            catalog: self.catalog,
+            #[cfg(feature = "catalog")]
+            catalog_lookup: self.catalog_lookup,
         }
     }
 }
@@ -46,49 +118,127 @@ impl Default for ClientBuilder {
         Self {
             api_key: None,
             api_version: String::from(API_VERSION),
+            base_url: String::from(BASE_URL),
+            user_country: None,
             http_client: reqwest::Client::default(),
+            #[cfg(feature = "catalog")]
+            catalog: None,
+            #[cfg(feature = "catalog")]
+            catalog_lookup: None,
         }
     }
 }
 
 #[derive(Clone)]
 pub struct OdesliClient {
-    api_key: Option<String>,
+    api_key: Option<ApiKey>,
     api_url: String,
+    user_country: Option<String>,
     http_client: reqwest::Client,
+    #[cfg(feature = "catalog")]
+    catalog: Option<Catalog>,
+    #[cfg(feature = "catalog")]
+    catalog_lookup: Option<Option<Duration>>,
 }
 
 impl OdesliClient {
-    async fn get(&self, mut params: Vec<(&str, &str)>) -> Result<LinksAPIResult, OdesliError> {
+    /// The URL of the links endpoint with `params`, the API key and the user
+    /// country.
+    fn links_url<'a>(&'a self, mut params: Vec<(&'a str, &'a str)>) -> Result<Url, OdesliError> {
         if let Some(key) = self.api_key.as_ref() {
-            params.push(("key", key.as_str()));
+            params.push(("key", key.expose()));
+        }
+        if let Some(country) = self.user_country.as_ref() {
+            params.push(("userCountry", country.as_str()));
         }
 
         let api_endpoint = format!("{}/{}", self.api_url, LINKS_ENDPOINT);
+        let mut url = Url::parse(&api_endpoint)
+            .map_err(|err| OdesliError::InvalidBaseUrl(format!("'{}': {}", self.api_url, err)))?;
+        url.query_pairs_mut().extend_pairs(params);
+        Ok(url)
+    }
 
-        match self
-            .http_client
-            .get(api_endpoint)
-            .query(params.as_slice())
-            .send()
-            .await
-        {
+    async fn get(&self, params: Vec<(&str, &str)>) -> Result<LinksAPIResult, OdesliError> {
+        let url = self.links_url(params)?;
+        let mut redacted_url = url.clone();
+        redact_url(&mut redacted_url);
+        log::debug!("GET {}", redacted_url);
+        let started = Instant::now();
+
+        match self.http_client.get(url).send().await {
             Ok(res) => {
                 let status_code = res.status();
-                let body = res.text().await.unwrap();
+                let body = res
+                    .text()
+                    .await
+                    .map_err(|err| OdesliError::ReqwestError(redact_error(err)))?;
+                log::debug!("{} from {} in {:?}", status_code, redacted_url, started.elapsed());
+                log::trace!("Response body: {}", body);
 
                 if status_code.as_u16() != 200 {
                     return Err(OdesliError::Non200StatusCode { status_code, body });
                 }
 
-                serde_json::from_str::<LinksAPIResult>(&body).map_err(|err| {
+                let result = serde_json::from_str::<LinksAPIResult>(&body).map_err(|err| {
                     OdesliError::ParseError {
                         error: err.to_string(),
                         body,
                     }
-                })
+                })?;
+
+                #[cfg(feature = "catalog")]
+                if let Some(catalog) = self.catalog.as_ref() {
+                    let recorded = result.clone();
+                    if let Err(err) =
+                        with_catalog(catalog, move |catalog| catalog.record(&recorded)).await
+                    {
+                        let unique_id = &result.entity_unique_id;
+                        log::debug!("Failed to record {} in the catalog: {}", unique_id, err);
+                    }
+                }
+
+                Ok(result)
+            }
+            Err(err) => {
+                let err = redact_error(err);
+                log::debug!("GET {} failed after {:?}: {}", redacted_url, started.elapsed(), err);
+                Err(OdesliError::ReqwestError(err))
+            }
+        }
+    }
+
+    /// The request [`OdesliClient::get_by_url`] sends, including the API key.
+    pub fn request_url_by_url(&self, url: &str) -> Result<Url, OdesliError> {
+        self.links_url(vec![("url", url)])
+    }
+
+    /// The request [`OdesliClient::get_by_id`] sends to the API, including the
+    /// API key. The catalog may answer before any request is sent.
+    pub fn request_url_by_id(
+        &self,
+        id: &str,
+        platform: &Platform,
+        entity_type: &EntityType,
+    ) -> Result<Url, OdesliError> {
+        self.links_url(vec![
+            ("id", id),
+            ("platform", platform.as_str()),
+            ("type", entity_type.as_str()),
+        ])
+    }
+
+    /// The request [`OdesliClient::get_by_input`] sends to the API, including
+    /// the API key.
+    pub fn request_url_by_input(&self, input: &ParsedInput) -> Result<Url, OdesliError> {
+        match (input.kind, input.lookup_id()) {
+            (InputKind::Url, _) | (InputKind::SongLinkPage, None) => {
+                self.request_url_by_url(&input.input)
             }
-            Err(err) => Err(OdesliError::ReqwestError(err)),
+            (_, Some((id, platform, entity_type))) => {
+                self.request_url_by_id(id, platform, entity_type)
+            }
+            (_, None) => Err(OdesliError::UnrecognizedInput(input.input.clone())),
         }
     }
 
@@ -114,6 +264,30 @@ impl OdesliClient {
         platform: &Platform,
         entity_type: &EntityType,
     ) -> Result<LinksAPIResult, OdesliError> {
+        #[cfg(feature = "catalog")]
+        if let (Some(catalog), Some(max_age)) = (self.catalog.as_ref(), self.catalog_lookup) {
+            let unique_id = EntityUniqueId::new(platform.api_provider(), entity_type, id);
+            let lookup = {
+                let (unique_id, country) = (unique_id.clone(), self.user_country.clone());
+                with_catalog(catalog, move |catalog| {
+                    catalog.lookup(&unique_id, country.as_deref(), max_age)
+                })
+                .await
+            };
+            // The catalog is only a cache, the API is called if it fails.
+            match lookup {
+                Ok(Some(result)) => match answer_for(result, &unique_id.to_string()) {
+                    Some(result) => {
+                        log::debug!("Catalog hit for {}", unique_id);
+                        return Ok(result);
+                    }
+                    None => log::debug!("Catalog result unrelated to {}", unique_id),
+                },
+                Ok(None) => log::debug!("Catalog miss for {}", unique_id),
+                Err(err) => log::debug!("Failed to look up {} in the catalog: {}", unique_id, err),
+            }
+        }
+
         self.get(vec![
             ("id", id),
             ("platform", platform.as_str()),
@@ -121,4 +295,100 @@ impl OdesliClient {
         ])
         .await
     }
+
+    /// Get a song/album from an input recognized by [`crate::parse_input`].
+    ///
+    /// URIs, entity unique IDs and song.link pages are looked up by ID, and
+    /// web URLs by URL.
+    pub async fn get_by_input(&self, input: &ParsedInput) -> Result<LinksAPIResult, OdesliError> {
+        match (input.kind, input.lookup_id()) {
+            (InputKind::Url, _) => self.get_by_url(&input.input).await,
+            (_, Some((id, platform, entity_type))) => {
+                self.get_by_id(id, platform, entity_type).await
+            }
+            (InputKind::SongLinkPage, None) => self.get_by_url(&input.input).await,
+            (_, None) => Err(OdesliError::UnrecognizedInput(input.input.clone())),
+        }
+    }
+}
+
+/// Answer a lookup of `unique_id` with `result`, found in the catalog through
+/// one of its equivalent entities: like the API, it must name the requested
+/// entity as its own. `None` if `result` does not mention `unique_id`.
+#[cfg(feature = "catalog")]
+fn answer_for(mut result: LinksAPIResult, unique_id: &str) -> Option<LinksAPIResult> {
+    let mentioned = result.entities_by_unique_id.contains_key(unique_id)
+        || result.links_by_platform.values().any(|link| link.entity_unique_id == unique_id);
+    if !mentioned {
+        return None;
+    }
+    result.entity_unique_id = unique_id.to_string();
+    Some(result)
+}
+
+/// Run `f` with `catalog` on a thread where blocking is allowed, so that the
+/// SQLite queries do not stall the async runtime.
+#[cfg(feature = "catalog")]
+async fn with_catalog<T: Send + 'static>(
+    catalog: &Catalog,
+    f: impl FnOnce(&Catalog) -> Result<T, OdesliError> + Send + 'static,
+) -> Result<T, OdesliError> {
+    let catalog = catalog.clone();
+    tokio::task::spawn_blocking(move || f(&catalog))
+        .await
+        .map_err(|err| OdesliError::IoError(err.into()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn invalid_base_urls_are_errors() {
+        let client = ClientBuilder::default().with_base_url("not a URL".to_string()).build();
+        let error = client.request_url_by_url("https://example.com").unwrap_err();
+        assert!(matches!(error, OdesliError::InvalidBaseUrl(_)), "{error}");
+        let error = client.get_by_url("https://example.com").await.unwrap_err();
+        assert!(matches!(error, OdesliError::InvalidBaseUrl(_)), "{error}");
+    }
+
+    #[cfg(feature = "catalog")]
+    #[tokio::test]
+    async fn catalog_failures_fall_back_to_the_api() {
+        let catalog = Catalog::open_in_memory().unwrap();
+        catalog
+            .with_connection(|connection| connection.execute_batch("DROP TABLE results"))
+            .unwrap();
+        let client = ClientBuilder::default()
+            .with_base_url("http://127.0.0.1:1".to_string())
+            .with_catalog(catalog)
+            .with_catalog_lookup(None)
+            .build();
+
+        let error = client.get_by_id("1", &Platform::Spotify, &EntityType::Song).await.unwrap_err();
+        assert!(matches!(error, OdesliError::ReqwestError(_)), "{error}");
+    }
+
+    #[cfg(feature = "catalog")]
+    #[tokio::test]
+    async fn catalog_hits_name_the_requested_entity() {
+        let catalog = Catalog::open_in_memory().unwrap();
+        let result = crate::testing::ResultBuilder::new("SPOTIFY_SONG::1")
+            .link(Platform::Spotify, "SPOTIFY_SONG::1", "https://open.spotify.com/track/1")
+            .link(Platform::Deezer, "DEEZER_SONG::1", "https://www.deezer.com/track/1")
+            .entity("SPOTIFY_SONG::1", "Sugar", "Maroon 5")
+            .entity("DEEZER_SONG::1", "Sugar", "Maroon 5")
+            .build();
+        catalog.record(&result).unwrap();
+        let client = ClientBuilder::default()
+            .with_base_url("http://127.0.0.1:1".to_string())
+            .with_catalog(catalog)
+            .with_catalog_lookup(None)
+            .build();
+
+        let found = client.get_by_id("1", &Platform::Deezer, &EntityType::Song).await.unwrap();
+        assert_eq!(found.entity_unique_id, "DEEZER_SONG::1");
+        let found = client.get_by_id("1", &Platform::Spotify, &EntityType::Song).await.unwrap();
+        assert_eq!(found.entity_unique_id, "SPOTIFY_SONG::1");
+    }
 }
@@ -0,0 +1,301 @@
+use std::collections::BTreeSet;
+
+use crate::{Entity, LinksAPIResult};
+
+use serde::{Deserialize, Serialize};
+
+/// Whether two results are for the same release, as decided by [`compare`].
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Verdict {
+    /// The results share entities or links, and none conflict.
+    Same,
+    /// The results are for a song and an album, or share nothing and their
+    /// titles or artists differ.
+    Different,
+    /// The evidence is missing or contradictory, e.g. the same title and
+    /// artist but different IDs on a platform.
+    Uncertain,
+}
+
+/// The outcome of [`compare`], with the reasons for its verdict.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Comparison {
+    pub verdict: Verdict,
+    /// Human readable reasons, the strongest evidence first.
+    pub reasons: Vec<String>,
+}
+
+/// How the titles and artists of two results compare.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum NameMatch {
+    /// Equal, ignoring case and punctuation.
+    Exact,
+    /// Equal once version qualifiers like "(Remastered)" are dropped too.
+    Loose,
+    Different,
+    /// A title or artist is missing.
+    Unknown,
+}
+
+/// Decide whether `a` and `b` are for the same release.
+///
+/// They are the same if they share an entity unique ID or a link on a
+/// platform. Links to different entities on the same platform are evidence of
+/// different releases. When neither decides, the normalized titles and
+/// artists of the entities looked up are compared. A song is never the same
+/// release as an album, whatever they share.
+pub fn compare(a: &LinksAPIResult, b: &LinksAPIResult) -> Comparison {
+    let (a_entity, b_entity) = (primary_entity(a), primary_entity(b));
+    let mut shared = Vec::new();
+    let mut conflicts = Vec::new();
+
+    if a.entity_unique_id == b.entity_unique_id {
+        shared.push(format!("both resolve to {}", a.entity_unique_id));
+    } else {
+        let a_ids = entity_unique_ids(a);
+        for unique_id in entity_unique_ids(b).intersection(&a_ids) {
+            shared.push(format!("both contain {}", unique_id));
+        }
+    }
+
+    for (platform, a_link) in a.links_by_platform.iter() {
+        let Some(b_link) = b.links_by_platform.get(platform) else {
+            continue;
+        };
+        let name = platform.display_name();
+        if a_link.url == b_link.url {
+            shared.push(format!("both link to {} on {}", a_link.url, name));
+        } else if a_link.entity_unique_id != b_link.entity_unique_id {
+            conflicts.push(format!(
+                "{} links to {} and {}",
+                name, a_link.entity_unique_id, b_link.entity_unique_id
+            ));
+        }
+    }
+
+    let names = compare_names(a_entity, b_entity);
+    let name_reason = match names {
+        NameMatch::Exact => "same title and artist",
+        NameMatch::Loose => "same title and artist, but for version qualifiers",
+        NameMatch::Different => "different titles or artists",
+        NameMatch::Unknown => "titles or artists are missing",
+    };
+
+    let type_conflict =
+        a_entity.zip(b_entity).filter(|(a, b)| a.entity_type != b.entity_type).map(|(a, b)| {
+            format!(
+                "different entity types, {} and {}",
+                a.entity_type.as_str(),
+                b.entity_type.as_str()
+            )
+        });
+
+    let verdict = match (shared.is_empty(), conflicts.is_empty(), names) {
+        _ if type_conflict.is_some() => Verdict::Different,
+        (false, true, _) => Verdict::Same,
+        (false, false, _) => Verdict::Uncertain,
+        (true, false, NameMatch::Exact | NameMatch::Loose) => Verdict::Uncertain,
+        (true, false, _) => Verdict::Different,
+        (true, true, NameMatch::Exact) => Verdict::Same,
+        (true, true, NameMatch::Different) => Verdict::Different,
+        (true, true, _) => Verdict::Uncertain,
+    };
+
+    let mut reasons = type_conflict.into_iter().collect::<Vec<_>>();
+    reasons.extend(shared);
+    reasons.extend(conflicts);
+    reasons.push(name_reason.to_string());
+    Comparison { verdict, reasons }
+}
+
+/// The unique IDs of all the entities in `result`.
+fn entity_unique_ids(result: &LinksAPIResult) -> BTreeSet<&str> {
+    result
+        .entities_by_unique_id
+        .keys()
+        .map(String::as_str)
+        .chain(result.links_by_platform.values().map(|link| link.entity_unique_id.as_str()))
+        .chain([result.entity_unique_id.as_str()])
+        .collect()
+}
+
+/// The entity `result` was looked up for, or else the one on the first
+/// platform having one.
+fn primary_entity(result: &LinksAPIResult) -> Option<&Entity> {
+    result.entities_by_unique_id.get(&result.entity_unique_id).or_else(|| {
+        result.links_by_platform.keys().find_map(|platform| result.get_platform_entity(platform))
+    })
+}
+
+fn compare_names(a: Option<&Entity>, b: Option<&Entity>) -> NameMatch {
+    let names = |entity: Option<&Entity>| {
+        let entity = entity?;
+        Some((entity.title.clone()?, entity.artist_name.clone()?))
+    };
+    let (Some((a_title, a_artist)), Some((b_title, b_artist))) = (names(a), names(b)) else {
+        return NameMatch::Unknown;
+    };
+
+    if normalize(&a_title) == normalize(&b_title) && normalize(&a_artist) == normalize(&b_artist) {
+        NameMatch::Exact
+    } else if normalize(&strip_qualifiers(&a_title)) == normalize(&strip_qualifiers(&b_title))
+        && normalize(&strip_qualifiers(&a_artist)) == normalize(&strip_qualifiers(&b_artist))
+    {
+        NameMatch::Loose
+    } else {
+        NameMatch::Different
+    }
+}
+
+/// `text` in lowercase, with only its letters and digits, separated by single
+/// spaces.
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `text` in lowercase, without the parts naming a version or featured
+/// artists, e.g. "(Remastered 2011)", "[Live]", "- Radio Edit" or
+/// "feat. Someone".
+fn strip_qualifiers(text: &str) -> String {
+    let mut stripped = String::new();
+    let mut depth = 0usize;
+    for c in text.to_lowercase().chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            c if depth == 0 => stripped.push(c),
+            _ => {}
+        }
+    }
+
+    let cut = [" - ", " feat. ", " feat ", " ft. ", " featuring "]
+        .iter()
+        .filter_map(|marker| stripped.find(marker))
+        .min();
+    if let Some(cut) = cut {
+        stripped.truncate(cut);
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::ResultBuilder, Platform};
+
+    /// A result looked up for `unique_id`, with `title` and `artist`, linking
+    /// to the entities of `links`.
+    fn result(
+        unique_id: &str,
+        title: &str,
+        artist: &str,
+        links: &[(Platform, &str)],
+    ) -> LinksAPIResult {
+        let mut builder = ResultBuilder::new(unique_id).entity(unique_id, title, artist);
+        for (platform, link_id) in links {
+            let url = format!("https://link.example/{link_id}");
+            builder = builder.link(platform.clone(), link_id, &url);
+        }
+        builder.build()
+    }
+
+    fn verdict(a: &LinksAPIResult, b: &LinksAPIResult) -> Verdict {
+        compare(a, b).verdict
+    }
+
+    #[test]
+    fn shared_evidence_without_conflicts_is_the_same() {
+        let a = result("SPOTIFY_SONG::1", "Sugar", "Maroon 5", &[]);
+        assert_eq!(verdict(&a, &a), Verdict::Same);
+        assert_eq!(compare(&a, &a).reasons[0], "both resolve to SPOTIFY_SONG::1");
+
+        // A shared link, even with other titles.
+        let deezer = [(Platform::Deezer, "DEEZER_SONG::9")];
+        let a = result("SPOTIFY_SONG::1", "Sugar", "Maroon 5", &deezer);
+        let b = result("SPOTIFY_SONG::2", "Sugar (Live)", "Maroon 5", &deezer);
+        assert_eq!(verdict(&a, &b), Verdict::Same);
+    }
+
+    #[test]
+    fn shared_evidence_with_conflicts_is_uncertain() {
+        let deezer = (Platform::Deezer, "DEEZER_SONG::7");
+        let a = result(
+            "SPOTIFY_SONG::1",
+            "Sugar",
+            "Maroon 5",
+            &[deezer.clone(), (Platform::Tidal, "8")],
+        );
+        let b = result("SPOTIFY_SONG::2", "Sugar", "Maroon 5", &[deezer, (Platform::Tidal, "9")]);
+        let comparison = compare(&a, &b);
+        assert_eq!(comparison.verdict, Verdict::Uncertain);
+        assert_eq!(
+            comparison.reasons,
+            [
+                "both contain DEEZER_SONG::7",
+                "both link to https://link.example/DEEZER_SONG::7 on Deezer",
+                "TIDAL links to 8 and 9",
+                "same title and artist",
+            ]
+        );
+    }
+
+    #[test]
+    fn conflicts_alone_depend_on_the_names() {
+        let b_links = [(Platform::Deezer, "DEEZER_SONG::9")];
+        let b = result("SPOTIFY_SONG::2", "Sugar", "Maroon 5", &b_links);
+        let a_links = [(Platform::Deezer, "DEEZER_SONG::8")];
+        let same_names = result("SPOTIFY_SONG::1", "Sugar - Remastered", "Maroon 5", &a_links);
+        assert_eq!(verdict(&same_names, &b), Verdict::Uncertain);
+        let other_names = result("SPOTIFY_SONG::1", "Memories", "Maroon 5", &a_links);
+        assert_eq!(verdict(&other_names, &b), Verdict::Different);
+    }
+
+    #[test]
+    fn nothing_shared_depends_on_the_names() {
+        let b = result("SPOTIFY_SONG::2", "Sugar", "Maroon 5", &[]);
+        let exact = result("SPOTIFY_SONG::1", "SUGAR!", "maroon 5", &[]);
+        assert_eq!(verdict(&exact, &b), Verdict::Same);
+        let loose = result("SPOTIFY_SONG::1", "Sugar (Remastered 2015)", "Maroon 5", &[]);
+        assert_eq!(verdict(&loose, &b), Verdict::Uncertain);
+        let different = result("SPOTIFY_SONG::1", "Memories", "Maroon 5", &[]);
+        assert_eq!(verdict(&different, &b), Verdict::Different);
+
+        let mut unknown = result("SPOTIFY_SONG::1", "Sugar", "Maroon 5", &[]);
+        unknown.entities_by_unique_id.clear();
+        let comparison = compare(&unknown, &b);
+        assert_eq!(comparison.verdict, Verdict::Uncertain);
+        assert_eq!(comparison.reasons, ["titles or artists are missing"]);
+    }
+
+    #[test]
+    fn songs_and_albums_are_different() {
+        let song = result("SPOTIFY_SONG::1", "Sugar", "Maroon 5", &[]);
+        let album = result(
+            "SPOTIFY_ALBUM::1",
+            "Sugar",
+            "Maroon 5",
+            &[(Platform::Deezer, "SPOTIFY_SONG::1")],
+        );
+        let comparison = compare(&song, &album);
+        assert_eq!(comparison.verdict, Verdict::Different);
+        assert_eq!(comparison.reasons[0], "different entity types, song and album");
+        assert_eq!(comparison.reasons[1], "both contain SPOTIFY_SONG::1");
+    }
+
+    #[test]
+    fn qualifiers_are_stripped() {
+        assert_eq!(strip_qualifiers("Sugar (Remastered 2015)"), "sugar ");
+        assert_eq!(strip_qualifiers("Sugar [Live] (feat. Someone)"), "sugar  ");
+        assert_eq!(strip_qualifiers("Sugar - Radio Edit"), "sugar");
+        assert_eq!(strip_qualifiers("Sugar feat. Someone"), "sugar");
+        assert_eq!(strip_qualifiers("Sugar ft. Someone - Live"), "sugar");
+        assert_eq!(strip_qualifiers("Sugar ((nested) still)) after"), "sugar  after");
+        assert_eq!(strip_qualifiers("Featuring"), "featuring");
+        assert_eq!(normalize("  Maroon   5! "), "maroon 5");
+    }
+}
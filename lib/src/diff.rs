@@ -0,0 +1,222 @@
+use crate::{Link, LinksAPIResult, Platform};
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+/// A single difference between two [`LinksAPIResult`]s.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "change", rename_all = "camelCase")]
+pub enum Change {
+    /// The Songlink page URL changed.
+    PageUrlChanged { old: String, new: String },
+    /// A platform is only present in the new result.
+    PlatformAdded { platform: Platform, link: Link },
+    /// A platform is only present in the old result.
+    PlatformRemoved { platform: Platform, link: Link },
+    /// The URL for a platform changed.
+    UrlChanged { platform: Platform, old: String, new: String },
+    /// A platform now points to a different entity.
+    EntityChanged {
+        platform: Platform,
+        #[serde(rename = "oldEntityUniqueId")]
+        old_entity_unique_id: String,
+        #[serde(rename = "newEntityUniqueId")]
+        new_entity_unique_id: String,
+    },
+    /// The title of the entity behind a platform changed.
+    TitleChanged { platform: Platform, old: Option<String>, new: Option<String> },
+    /// The artist name of the entity behind a platform changed.
+    ArtistChanged { platform: Platform, old: Option<String>, new: Option<String> },
+}
+
+/// The set of [`Change`]s between two [`LinksAPIResult`]s, as returned by
+/// [`diff`].
+///
+/// Changes are ordered by platform (in the order of [`Platform::iter`]), with
+/// the page URL change, if any, first.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct LinksDiff {
+    pub changes: Vec<Change>,
+}
+
+impl LinksDiff {
+    /// Whether the two results were equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// The platforms that are only present in the new result.
+    pub fn added_platforms(&self) -> impl Iterator<Item = &Platform> {
+        self.changes.iter().filter_map(|change| match change {
+            Change::PlatformAdded { platform, .. } => Some(platform),
+            _ => None,
+        })
+    }
+
+    /// The platforms that are only present in the old result.
+    pub fn removed_platforms(&self) -> impl Iterator<Item = &Platform> {
+        self.changes.iter().filter_map(|change| match change {
+            Change::PlatformRemoved { platform, .. } => Some(platform),
+            _ => None,
+        })
+    }
+}
+
+/// Compute the structural differences going from `old` to `new`.
+///
+/// # Arguments
+///
+/// * `old`: The earlier snapshot.
+/// * `new`: The later snapshot.
+pub fn diff(old: &LinksAPIResult, new: &LinksAPIResult) -> LinksDiff {
+    let mut changes = Vec::new();
+
+    if old.page_url != new.page_url {
+        changes
+            .push(Change::PageUrlChanged { old: old.page_url.clone(), new: new.page_url.clone() });
+    }
+
+    for platform in Platform::iter() {
+        let (old_link, new_link) =
+            match (old.get_platform_url(&platform), new.get_platform_url(&platform)) {
+                (None, None) => continue,
+                (None, Some(link)) => {
+                    changes.push(Change::PlatformAdded { platform, link: link.clone() });
+                    continue;
+                }
+                (Some(link), None) => {
+                    changes.push(Change::PlatformRemoved { platform, link: link.clone() });
+                    continue;
+                }
+                (Some(old_link), Some(new_link)) => (old_link, new_link),
+            };
+
+        if old_link.url != new_link.url {
+            changes.push(Change::UrlChanged {
+                platform: platform.clone(),
+                old: old_link.url.clone(),
+                new: new_link.url.clone(),
+            });
+        }
+        if old_link.entity_unique_id != new_link.entity_unique_id {
+            changes.push(Change::EntityChanged {
+                platform: platform.clone(),
+                old_entity_unique_id: old_link.entity_unique_id.clone(),
+                new_entity_unique_id: new_link.entity_unique_id.clone(),
+            });
+        }
+
+        let old_entity = old.get_platform_entity(&platform);
+        let new_entity = new.get_platform_entity(&platform);
+        let old_title = old_entity.and_then(|entity| entity.title.clone());
+        let new_title = new_entity.and_then(|entity| entity.title.clone());
+        if old_title != new_title {
+            changes.push(Change::TitleChanged {
+                platform: platform.clone(),
+                old: old_title,
+                new: new_title,
+            });
+        }
+        let old_artist = old_entity.and_then(|entity| entity.artist_name.clone());
+        let new_artist = new_entity.and_then(|entity| entity.artist_name.clone());
+        if old_artist != new_artist {
+            changes.push(Change::ArtistChanged { platform, old: old_artist, new: new_artist });
+        }
+    }
+
+    LinksDiff { changes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ResultBuilder;
+
+    const SPOTIFY: &str = "SPOTIFY_SONG::1";
+    const DEEZER: &str = "DEEZER_SONG::2";
+
+    fn base() -> ResultBuilder {
+        ResultBuilder::new(SPOTIFY)
+            .link(Platform::Spotify, SPOTIFY, "https://open.spotify.com/track/1")
+            .entity(SPOTIFY, "Sugar", "Maroon 5")
+    }
+
+    #[test]
+    fn equal_results_have_no_changes() {
+        assert!(diff(&base().build(), &base().build()).is_empty());
+    }
+
+    #[test]
+    fn added_and_removed_platforms() {
+        let old = base().link(Platform::Tidal, "TIDAL_SONG::3", "https://tidal.com/track/3");
+        let new = base()
+            .link(Platform::Deezer, DEEZER, "https://www.deezer.com/track/2")
+            .entity(DEEZER, "Sugar", "Maroon 5");
+        let diff = diff(&old.build(), &new.build());
+
+        assert_eq!(diff.added_platforms().collect::<Vec<_>>(), [&Platform::Deezer]);
+        assert_eq!(diff.removed_platforms().collect::<Vec<_>>(), [&Platform::Tidal]);
+        assert!(matches!(
+            &diff.changes[..],
+            [Change::PlatformAdded { link, .. }, Change::PlatformRemoved { .. }]
+                if link.url == "https://www.deezer.com/track/2"
+        ));
+    }
+
+    #[test]
+    fn changes_on_a_platform() {
+        let old = base().build();
+        let mut new = ResultBuilder::new(SPOTIFY)
+            .link(Platform::Spotify, "SPOTIFY_SONG::9", "https://open.spotify.com/track/9")
+            .entity("SPOTIFY_SONG::9", "Sugar (Remix)", "Maroon 5")
+            .build();
+        new.page_url = "https://song.link/s/9".to_string();
+
+        let changes = diff(&old, &new).changes;
+        assert!(matches!(&changes[0], Change::PageUrlChanged { new, .. } if new.ends_with("/9")));
+        assert!(matches!(&changes[1], Change::UrlChanged { platform: Platform::Spotify, .. }));
+        assert!(matches!(
+            &changes[2],
+            Change::EntityChanged { old_entity_unique_id, new_entity_unique_id, .. }
+                if old_entity_unique_id == SPOTIFY && new_entity_unique_id == "SPOTIFY_SONG::9"
+        ));
+        assert!(matches!(
+            &changes[3],
+            Change::TitleChanged { old: Some(old), new: Some(new), .. }
+                if old == "Sugar" && new == "Sugar (Remix)"
+        ));
+        assert_eq!(changes.len(), 4, "{changes:?}");
+    }
+
+    #[test]
+    fn artist_changes_are_per_platform() {
+        let old = base().build();
+        let new = ResultBuilder::new(SPOTIFY)
+            .link(Platform::Spotify, SPOTIFY, "https://open.spotify.com/track/1")
+            .entity(SPOTIFY, "Sugar", "Maroon Five")
+            .build();
+
+        let changes = diff(&old, &new).changes;
+        assert!(matches!(
+            &changes[..],
+            [Change::ArtistChanged { platform: Platform::Spotify, new: Some(new), .. }]
+                if new == "Maroon Five"
+        ));
+    }
+
+    #[test]
+    fn changes_follow_the_platform_order() {
+        let old = ResultBuilder::new(SPOTIFY).build();
+        let new = ResultBuilder::new(SPOTIFY)
+            .link(Platform::Tidal, "TIDAL_SONG::3", "https://tidal.com/track/3")
+            .link(Platform::Spotify, SPOTIFY, "https://open.spotify.com/track/1")
+            .link(Platform::Deezer, DEEZER, "https://www.deezer.com/track/2")
+            .build();
+
+        let added = diff(&old, &new).added_platforms().cloned().collect::<Vec<_>>();
+        let mut expected = added.clone();
+        expected.sort();
+        assert_eq!(added, expected);
+        assert_eq!(added.len(), 3);
+    }
+}
@@ -0,0 +1,66 @@
+use std::str::FromStr;
+
+use crate::{APIProvider, EntityType, OdesliError};
+
+/// A parsed entity unique ID, as used for the keys of
+/// [`crate::LinksAPIResult::entities_by_unique_id`], e.g.
+/// `SPOTIFY_SONG::7CNUefGBVLn4cLoYv3ej8x`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct EntityUniqueId {
+    /// The API provider the entity comes from.
+    pub provider: APIProvider,
+    /// The kind of the entity as written in the ID, e.g. `SONG`, `ALBUM` or
+    /// `VIDEO`.
+    pub kind: String,
+    /// The ID of the entity on the API provider.
+    pub id: String,
+}
+
+impl EntityUniqueId {
+    /// Build the unique ID Odesli uses for an entity of a given type on a
+    /// given provider.
+    pub fn new(provider: APIProvider, entity_type: &EntityType, id: &str) -> Self {
+        let kind = match (&provider, entity_type) {
+            // YouTube has no notion of songs, every match is a video.
+            (APIProvider::YouTube, EntityType::Song) => "VIDEO",
+            (_, EntityType::Song) => "SONG",
+            (_, EntityType::Album) => "ALBUM",
+        };
+        Self { provider, kind: kind.to_string(), id: id.to_string() }
+    }
+
+    /// The [`EntityType`] this ID refers to, if known.
+    pub fn entity_type(&self) -> Option<EntityType> {
+        match self.kind.as_str() {
+            "SONG" | "VIDEO" => Some(EntityType::Song),
+            "ALBUM" => Some(EntityType::Album),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for EntityUniqueId {
+    type Err = OdesliError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || OdesliError::InvalidEntityUniqueId(value.to_string());
+
+        let (prefix, id) = value.split_once("::").ok_or_else(invalid)?;
+        let (provider, kind) = prefix.rsplit_once('_').ok_or_else(invalid)?;
+        if id.is_empty() || kind.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            provider: APIProvider::from_str(&provider.to_lowercase())?,
+            kind: kind.to_string(),
+            id: id.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for EntityUniqueId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}_{}::{}", self.provider.as_str().to_uppercase(), self.kind, self.id)
+    }
+}
@@ -1,7 +1,10 @@
 use reqwest::StatusCode;
 
 /// Various error kinds that the library can return.
+///
+/// New kinds may be added in minor releases.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum OdesliError {
     /// An unknown [`crate::EntityType`] value was passed.
     ///
@@ -11,6 +14,16 @@ pub enum OdesliError {
     UnknownPlatform(String),
     /// An unknown [`crate::APIProvider`] value was passed.
     UnknownAPIProvider(String),
+    /// A malformed [`crate::EntityUniqueId`] was passed.
+    ///
+    /// Entity unique IDs look like `SPOTIFY_SONG::7CNUefGBVLn4cLoYv3ej8x`.
+    InvalidEntityUniqueId(String),
+    /// An input was neither a URL, a platform URI nor an entity unique ID, see
+    /// [`crate::parse_input`].
+    UnrecognizedInput(String),
+    /// The base URL given to [`crate::ClientBuilder::with_base_url`] is not a
+    /// valid URL.
+    InvalidBaseUrl(String),
     /// Failed to JSON parse the response from Odesli API.
     ParseError {
         /// The error encountered while parsing the body.
@@ -26,7 +39,23 @@ pub enum OdesliError {
         body: String,
     },
     /// Error returned by [`reqwest::Client`] on sending a request.
+    ///
+    /// The API key is redacted from the URL of the request it carries.
     ReqwestError(reqwest::Error),
+    /// A downloaded file was not of an accepted `Content-Type`.
+    UnexpectedContentType(String),
+    /// A downloaded thumbnail exceeded the configured size limit.
+    ThumbnailTooLarge {
+        /// The size limit (in bytes) that was exceeded.
+        limit: u64,
+    },
+    /// Failed to get the API key from the configured file or command.
+    ApiKeySourceError(String),
+    /// Failed to read or write a local file.
+    IoError(std::io::Error),
+    /// Error returned by the SQLite database backing a [`crate::Catalog`].
+    #[cfg(feature = "catalog")]
+    CatalogError(rusqlite::Error),
 }
 
 impl std::fmt::Display for OdesliError {
@@ -38,14 +67,38 @@ impl std::fmt::Display for OdesliError {
                 Self::UnknownEntityType(entity) => format!("Unknown EntityType: {entity}",),
                 Self::UnknownPlatform(platform) => format!("Unknown Platform: {platform}"),
                 Self::UnknownAPIProvider(provider) => format!("Unknown APIProvider: {provider}"),
+                Self::InvalidEntityUniqueId(unique_id) =>
+                    format!("Invalid entity unique ID: {unique_id}"),
+                Self::UnrecognizedInput(input) => format!(
+                    "Unrecognized input: {input}, expected a URL, a platform URI or an entity unique ID"
+                ),
+                Self::InvalidBaseUrl(error) => format!("Invalid base URL {error}"),
                 Self::ParseError { error, .. } =>
                     format!("Failed to JSON parse the response body: {error}"),
                 Self::Non200StatusCode { status_code, .. } =>
                     format!("Received non-200 status code by Odesli: {status_code}"),
                 Self::ReqwestError(error) => format!("Failed to make HTTP request: {error}"),
+                Self::UnexpectedContentType(content_type) =>
+                    format!("Unexpected Content-Type: {content_type}"),
+                Self::ThumbnailTooLarge { limit } =>
+                    format!("Thumbnail is larger than the limit of {limit} bytes"),
+                Self::ApiKeySourceError(error) => format!("Failed to get the API key: {error}"),
+                Self::IoError(error) => format!("Failed to access a local file: {error}"),
+                #[cfg(feature = "catalog")]
+                Self::CatalogError(error) => format!("Catalog query failed: {error}"),
             }
         )
     }
 }
 
-impl std::error::Error for OdesliError {}
+impl std::error::Error for OdesliError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ReqwestError(error) => Some(error),
+            Self::IoError(error) => Some(error),
+            #[cfg(feature = "catalog")]
+            Self::CatalogError(error) => Some(error),
+            _ => None,
+        }
+    }
+}
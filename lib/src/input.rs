@@ -0,0 +1,390 @@
+use std::str::FromStr;
+
+use reqwest::Url;
+use serde::Serialize;
+
+use crate::{EntityType, EntityUniqueId, OdesliError, Platform};
+
+/// The kinds of inputs [`parse_input`] recognizes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InputKind {
+    /// A web URL, e.g. `https://open.spotify.com/track/7CNUefGBVLn4cLoYv3ej8x`.
+    Url,
+    /// A platform URI, e.g. `spotify:track:7CNUefGBVLn4cLoYv3ej8x`.
+    Uri,
+    /// An entity unique ID, e.g. `SPOTIFY_SONG::7CNUefGBVLn4cLoYv3ej8x`.
+    UniqueId,
+    /// A song.link or album.link page, e.g.
+    /// `https://song.link/s/7CNUefGBVLn4cLoYv3ej8x`.
+    SongLinkPage,
+}
+
+/// An input recognized offline by [`parse_input`], with as much as could be
+/// learned from it.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedInput {
+    /// The input, with `https://` prepended to URLs given without a scheme.
+    pub input: String,
+    pub kind: InputKind,
+    /// The platform the input refers to, if recognized.
+    pub platform: Option<Platform>,
+    /// The type of the entity the input refers to, if recognized.
+    pub entity_type: Option<EntityType>,
+    /// The ID of the entity on `platform`, if recognized.
+    pub id: Option<String>,
+}
+
+impl ParsedInput {
+    /// The platform, type and ID of the entity, if they are all known, to look
+    /// it up with [`crate::OdesliClient::get_by_id`].
+    pub fn lookup_id(&self) -> Option<(&str, &Platform, &EntityType)> {
+        match (self.id.as_deref(), self.platform.as_ref(), self.entity_type.as_ref()) {
+            (Some(id), Some(platform), Some(entity_type)) => Some((id, platform, entity_type)),
+            _ => None,
+        }
+    }
+
+    /// The unique ID Odesli gives the entity, if its platform, type and ID are
+    /// known.
+    pub fn entity_unique_id(&self) -> Option<EntityUniqueId> {
+        let (id, platform, entity_type) = self.lookup_id()?;
+        Some(EntityUniqueId::new(platform.api_provider(), entity_type, id))
+    }
+
+    /// The usual URL of the entity on its platform, if its platform, type and
+    /// ID are known and the platform has URLs built from the ID alone.
+    pub fn canonical_url(&self) -> Option<String> {
+        let (id, platform, entity_type) = self.lookup_id()?;
+        let song = *entity_type == EntityType::Song;
+        let url = match platform {
+            Platform::Spotify if song => format!("https://open.spotify.com/track/{id}"),
+            Platform::Spotify => format!("https://open.spotify.com/album/{id}"),
+            Platform::AppleMusic if song => format!("https://music.apple.com/song/{id}"),
+            Platform::AppleMusic => format!("https://music.apple.com/album/{id}"),
+            // Albums are playlists on YouTube.
+            Platform::YouTube if song => format!("https://www.youtube.com/watch?v={id}"),
+            Platform::YouTube => format!("https://www.youtube.com/playlist?list={id}"),
+            Platform::YouTubeMusic if song => format!("https://music.youtube.com/watch?v={id}"),
+            Platform::YouTubeMusic => format!("https://music.youtube.com/playlist?list={id}"),
+            Platform::Deezer if song => format!("https://www.deezer.com/track/{id}"),
+            Platform::Deezer => format!("https://www.deezer.com/album/{id}"),
+            Platform::Tidal if song => format!("https://tidal.com/browse/track/{id}"),
+            Platform::Tidal => format!("https://tidal.com/browse/album/{id}"),
+            Platform::AmazonMusic if !song => format!("https://music.amazon.com/albums/{id}"),
+            Platform::Yandex if !song => format!("https://music.yandex.com/album/{id}"),
+            Platform::Anghami if song => format!("https://play.anghami.com/song/{id}"),
+            Platform::Anghami => format!("https://play.anghami.com/album/{id}"),
+            Platform::Boomplay if song => format!("https://www.boomplay.com/songs/{id}"),
+            Platform::Boomplay => format!("https://www.boomplay.com/albums/{id}"),
+            _ => return None,
+        };
+        Some(url)
+    }
+}
+
+/// Recognize what `input` refers to without calling the API: a web URL, a
+/// platform URI like `spotify:track:<id>`, an entity unique ID like
+/// `SPOTIFY_SONG::<id>`, or a song.link page.
+///
+/// URLs of unknown websites are accepted as is, as the API may still know
+/// them. URLs may be given without their `https://`.
+pub fn parse_input(input: &str) -> Result<ParsedInput, OdesliError> {
+    let input = input.trim();
+    let unrecognized = || OdesliError::UnrecognizedInput(input.to_string());
+
+    if input.contains("::") {
+        let unique_id = EntityUniqueId::from_str(input)?;
+        return Ok(ParsedInput {
+            input: input.to_string(),
+            kind: InputKind::UniqueId,
+            platform: Some(unique_id.provider.platform()),
+            entity_type: unique_id.entity_type(),
+            id: Some(unique_id.id),
+        });
+    }
+
+    if let Some(uri) = input.strip_prefix("spotify:") {
+        let (entity_type, id) = match uri.split(':').collect::<Vec<_>>()[..] {
+            ["track", id] if !id.is_empty() => (EntityType::Song, id),
+            ["album", id] if !id.is_empty() => (EntityType::Album, id),
+            _ => return Err(unrecognized()),
+        };
+        return Ok(ParsedInput {
+            input: input.to_string(),
+            kind: InputKind::Uri,
+            platform: Some(Platform::Spotify),
+            entity_type: Some(entity_type),
+            id: Some(id.to_string()),
+        });
+    }
+
+    let url = if input.contains("://") {
+        input.to_string()
+    } else if input.split('/').next().is_some_and(|host| host.contains('.')) {
+        format!("https://{input}")
+    } else {
+        return Err(unrecognized());
+    };
+    let parsed = Url::parse(&url).map_err(|_| unrecognized())?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(unrecognized());
+    }
+    Ok(parse_url(url, &parsed))
+}
+
+fn parse_url(input: String, url: &Url) -> ParsedInput {
+    let host = url.host_str().unwrap_or_default().to_lowercase();
+    let host = host.strip_prefix("www.").or_else(|| host.strip_prefix("m.")).unwrap_or(&host);
+    let segments = url
+        .path_segments()
+        .map(|segments| segments.filter(|segment| !segment.is_empty()).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let query = |name: &str| {
+        url.query_pairs().find(|(key, _)| key == name).map(|(_, value)| value.into_owned())
+    };
+
+    let mut parsed =
+        ParsedInput { input, kind: InputKind::Url, platform: None, entity_type: None, id: None };
+    let mut found = |platform: Platform, entity: Option<(EntityType, String)>| {
+        parsed.platform = Some(platform);
+        if let Some((entity_type, id)) = entity {
+            parsed.entity_type = Some(entity_type);
+            parsed.id = Some(id);
+        }
+    };
+
+    match host {
+        "song.link" | "album.link" => {
+            let entity_type =
+                if host == "song.link" { EntityType::Song } else { EntityType::Album };
+            let platform = match segments[..] {
+                ["s", _] => Some(Platform::Spotify),
+                ["i", _] => Some(Platform::iTunes),
+                _ => None,
+            };
+            if let Some(platform) = platform {
+                found(platform, Some((entity_type, segments[1].to_string())));
+            }
+            parsed.kind = InputKind::SongLinkPage;
+        }
+        "open.spotify.com" | "play.spotify.com" => {
+            // Localized URLs start with e.g. `intl-de`.
+            let segments = match segments.first() {
+                Some(first) if first.starts_with("intl-") => &segments[1..],
+                _ => &segments[..],
+            };
+            found(Platform::Spotify, typed_id(segments, "track", "album"));
+        }
+        "music.apple.com" | "geo.music.apple.com" | "itunes.apple.com" => {
+            let platform =
+                if host == "itunes.apple.com" { Platform::iTunes } else { Platform::AppleMusic };
+            // `/<country>/album/<name>/<id>?i=<track id>` or `/<country>/song/<name>/<id>`.
+            let id = segments.last().map(|id| id.trim_start_matches("id").to_string());
+            let entity = match (segments.iter().find(|s| ["album", "song"].contains(s)), id) {
+                (Some(&"album"), Some(id)) => match query("i") {
+                    Some(track_id) => Some((EntityType::Song, track_id)),
+                    None => Some((EntityType::Album, id)),
+                },
+                (Some(&"song"), Some(id)) => Some((EntityType::Song, id)),
+                _ => None,
+            };
+            found(platform, entity);
+        }
+        "youtube.com" | "youtu.be" | "music.youtube.com" => {
+            let platform = if host == "music.youtube.com" {
+                Platform::YouTubeMusic
+            } else {
+                Platform::YouTube
+            };
+            let id = match (host, &segments[..]) {
+                ("youtu.be", [id]) => Some(id.to_string()),
+                (_, ["watch"]) => query("v"),
+                _ => None,
+            };
+            found(platform, id.map(|id| (EntityType::Song, id)));
+        }
+        "deezer.com" => {
+            // Localized URLs start with the language, e.g. `/fr/track/<id>`.
+            let segments = match segments.first() {
+                Some(first) if first.len() == 2 => &segments[1..],
+                _ => &segments[..],
+            };
+            found(Platform::Deezer, typed_id(segments, "track", "album"));
+        }
+        "tidal.com" | "listen.tidal.com" => {
+            let segments = match segments.first() {
+                Some(&"browse") => &segments[1..],
+                _ => &segments[..],
+            };
+            found(Platform::Tidal, typed_id(segments, "track", "album"));
+        }
+        "music.amazon.com" => {
+            let entity = match (&segments[..], query("trackAsin")) {
+                (["albums", _], Some(track_id)) => Some((EntityType::Song, track_id)),
+                (["albums", id], None) => Some((EntityType::Album, id.to_string())),
+                _ => None,
+            };
+            found(Platform::AmazonMusic, entity);
+        }
+        "amazon.com" => found(Platform::AmazonStore, None),
+        "play.google.com" => found(Platform::GoogleStore, None),
+        "soundcloud.com" => found(Platform::SoundCloud, None),
+        "pandora.com" => found(Platform::Pandora, None),
+        "napster.com" | "play.napster.com" => found(Platform::Napster, None),
+        "music.yandex.ru" | "music.yandex.com" => {
+            let entity = match segments[..] {
+                ["album", _, "track", id] => Some((EntityType::Song, id.to_string())),
+                ["album", id] => Some((EntityType::Album, id.to_string())),
+                _ => None,
+            };
+            found(Platform::Yandex, entity);
+        }
+        "spinrilla.com" => found(Platform::Spinrilla, None),
+        "audius.co" => found(Platform::Audius, None),
+        "anghami.com" | "play.anghami.com" => {
+            found(Platform::Anghami, typed_id(&segments, "song", "album"))
+        }
+        "boomplay.com" => found(Platform::Boomplay, typed_id(&segments, "songs", "albums")),
+        "audiomack.com" => found(Platform::Audiomack, None),
+        host if host.ends_with(".bandcamp.com") => found(Platform::Bandcamp, None),
+        _ => {}
+    }
+
+    parsed
+}
+
+/// Read `[<song>, <id>]` or `[<album>, <id>]` path segments.
+fn typed_id(segments: &[&str], song: &str, album: &str) -> Option<(EntityType, String)> {
+    match segments {
+        [kind, id, ..] if *kind == song => Some((EntityType::Song, id.to_string())),
+        [kind, id, ..] if *kind == album => Some((EntityType::Album, id.to_string())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The kind, platform, type and ID `input` is parsed into.
+    fn parse(input: &str) -> (InputKind, Option<Platform>, Option<EntityType>, Option<String>) {
+        let parsed = parse_input(input).unwrap();
+        (parsed.kind, parsed.platform, parsed.entity_type, parsed.id)
+    }
+
+    fn url(
+        platform: Option<Platform>,
+        entity: Option<(EntityType, &str)>,
+    ) -> (InputKind, Option<Platform>, Option<EntityType>, Option<String>) {
+        let (entity_type, id) = entity.map(|(t, id)| (t, id.to_string())).unzip();
+        (InputKind::Url, platform, entity_type, id)
+    }
+
+    #[test]
+    fn unique_ids_and_uris() {
+        assert_eq!(
+            parse(" SPOTIFY_SONG::7CNUefGBVLn4cLoYv3ej8x "),
+            (
+                InputKind::UniqueId,
+                Some(Platform::Spotify),
+                Some(EntityType::Song),
+                Some("7CNUefGBVLn4cLoYv3ej8x".to_string())
+            )
+        );
+        assert_eq!(
+            parse("spotify:album:1"),
+            (
+                InputKind::Uri,
+                Some(Platform::Spotify),
+                Some(EntityType::Album),
+                Some("1".to_string())
+            )
+        );
+        for input in ["spotify:artist:1", "spotify:track:", "UNKNOWN_SONG::1", "sugar", ""] {
+            assert!(parse_input(input).is_err(), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn platform_urls() {
+        use EntityType::{Album, Song};
+        let cases = [
+            ("open.spotify.com/intl-de/track/1?si=x", Platform::Spotify, Some((Song, "1"))),
+            (
+                "https://music.apple.com/us/album/sugar/2?i=3",
+                Platform::AppleMusic,
+                Some((Song, "3")),
+            ),
+            ("https://music.apple.com/us/album/v/2", Platform::AppleMusic, Some((Album, "2"))),
+            ("https://itunes.apple.com/us/album/v/id2", Platform::iTunes, Some((Album, "2"))),
+            ("https://m.youtube.com/watch?v=4", Platform::YouTube, Some((Song, "4"))),
+            ("https://youtu.be/4", Platform::YouTube, Some((Song, "4"))),
+            ("https://music.youtube.com/watch?v=4", Platform::YouTubeMusic, Some((Song, "4"))),
+            ("https://www.deezer.com/fr/album/5", Platform::Deezer, Some((Album, "5"))),
+            ("https://listen.tidal.com/track/6", Platform::Tidal, Some((Song, "6"))),
+            ("https://tidal.com/browse/album/6", Platform::Tidal, Some((Album, "6"))),
+            (
+                "https://music.amazon.com/albums/A?trackAsin=B",
+                Platform::AmazonMusic,
+                Some((Song, "B")),
+            ),
+            ("https://music.yandex.ru/album/7/track/8", Platform::Yandex, Some((Song, "8"))),
+            ("https://play.anghami.com/album/9", Platform::Anghami, Some((Album, "9"))),
+            ("https://www.boomplay.com/songs/10", Platform::Boomplay, Some((Song, "10"))),
+            ("https://soundcloud.com/maroon-5/sugar", Platform::SoundCloud, None),
+            ("https://maroon5.bandcamp.com/track/sugar", Platform::Bandcamp, None),
+        ];
+        for (input, platform, entity) in cases {
+            assert_eq!(parse(input), url(Some(platform), entity), "{input}");
+        }
+        // The API may still know the URLs of other websites.
+        assert_eq!(parse("example.com/sugar"), url(None, None));
+        assert_eq!(parse_input("example.com/sugar").unwrap().input, "https://example.com/sugar");
+        assert!(parse_input("ftp://example.com/sugar").is_err());
+    }
+
+    #[test]
+    fn song_link_pages() {
+        let parsed = parse_input("https://song.link/s/1").unwrap();
+        assert_eq!(parsed.kind, InputKind::SongLinkPage);
+        assert_eq!(parsed.entity_unique_id().unwrap().to_string(), "SPOTIFY_SONG::1");
+        let parsed = parse_input("https://album.link/i/2").unwrap();
+        assert_eq!(parsed.entity_unique_id().unwrap().to_string(), "ITUNES_ALBUM::2");
+        // Pages by short code need the API.
+        let parsed = parse_input("https://song.link/abcdef").unwrap();
+        assert_eq!((parsed.kind, parsed.lookup_id()), (InputKind::SongLinkPage, None));
+    }
+
+    #[test]
+    fn canonical_urls() {
+        let canonical = |input: &str| parse_input(input).unwrap().canonical_url();
+        let cases = [
+            ("spotify:track:1", Some("https://open.spotify.com/track/1")),
+            ("SPOTIFY_ALBUM::1", Some("https://open.spotify.com/album/1")),
+            ("https://music.apple.com/us/album/v/2?i=3", Some("https://music.apple.com/song/3")),
+            ("https://youtu.be/4", Some("https://www.youtube.com/watch?v=4")),
+            ("YOUTUBE_ALBUM::OLAK5uy_x", Some("https://www.youtube.com/playlist?list=OLAK5uy_x")),
+            ("https://music.youtube.com/watch?v=4", Some("https://music.youtube.com/watch?v=4")),
+            ("https://www.deezer.com/en/track/5", Some("https://www.deezer.com/track/5")),
+            ("https://tidal.com/browse/album/6", Some("https://tidal.com/browse/album/6")),
+            ("AMAZON_ALBUM::A", Some("https://music.amazon.com/albums/A")),
+            ("https://music.yandex.ru/album/7/track/8", None),
+            ("https://soundcloud.com/maroon-5/sugar", None),
+            ("https://example.com/sugar", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(canonical(input).as_deref(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn canonical_urls_parse_back() {
+        for input in ["spotify:track:1", "DEEZER_ALBUM::5", "TIDAL_SONG::6", "ANGHAMI_SONG::9"] {
+            let parsed = parse_input(input).unwrap();
+            let reparsed = parse_input(&parsed.canonical_url().unwrap()).unwrap();
+            assert_eq!(reparsed.entity_unique_id(), parsed.entity_unique_id(), "{input}");
+        }
+    }
+}
@@ -1,13 +1,40 @@
+pub use api_key::*;
 pub use api_result::*;
+#[cfg(feature = "catalog")]
+pub use catalog::*;
 pub use client::*;
+pub use compare::*;
 pub use consts::*;
+pub use diff::*;
 pub use entity_type::*;
+pub use entity_unique_id::*;
 pub use errors::*;
+pub use input::*;
+pub use merge::*;
 pub use platforms::*;
+pub use search::*;
+#[cfg(feature = "thumbnails")]
+pub use thumbnail_cache::*;
 
+/// The type of the request URLs returned by [`OdesliClient`].
+pub use reqwest::Url;
+
+mod api_key;
 mod api_result;
+#[cfg(feature = "catalog")]
+mod catalog;
 mod client;
+mod compare;
 mod consts;
+mod diff;
 mod entity_type;
+mod entity_unique_id;
 mod errors;
+mod input;
+mod merge;
 mod platforms;
+mod search;
+#[cfg(test)]
+mod testing;
+#[cfg(feature = "thumbnails")]
+mod thumbnail_cache;
@@ -0,0 +1,197 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{LinksAPIResult, Platform};
+
+use serde::{Deserialize, Serialize};
+
+/// How to resolve a platform (or entity) present in more than one of the
+/// results passed to [`merge`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Keep the value from the earliest result that has it.
+    #[default]
+    KeepFirst,
+    /// Keep the value from the latest result that has it.
+    KeepLast,
+    /// Keep the value from the earliest result whose `user_country` matches
+    /// the given country, falling back to [`MergePolicy::KeepFirst`].
+    PreferCountry(String),
+}
+
+impl MergePolicy {
+    /// Whether the value from `candidate` should replace the one taken from
+    /// `current`.
+    fn should_replace(&self, current: &LinksAPIResult, candidate: &LinksAPIResult) -> bool {
+        match self {
+            Self::KeepFirst => false,
+            Self::KeepLast => true,
+            Self::PreferCountry(country) => {
+                current.user_country != *country && candidate.user_country == *country
+            }
+        }
+    }
+}
+
+/// The result a link in a [`MergedLinksResult`] was taken from.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct LinkSource {
+    /// Position of the source in the slice passed to [`merge`].
+    pub index: usize,
+    /// The `entity_unique_id` of the source result, i.e. what it was looked
+    /// up by.
+    #[serde(rename = "entityUniqueId")]
+    pub entity_unique_id: String,
+    /// The `user_country` of the source result.
+    #[serde(rename = "userCountry")]
+    pub user_country: String,
+}
+
+/// The union of several [`LinksAPIResult`]s, as returned by [`merge`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MergedLinksResult {
+    /// The merged result. Its `entity_unique_id`, `user_country` and
+    /// `page_url` are taken from the first source.
+    pub result: LinksAPIResult,
+
+    /// For each platform in `result.links_by_platform`, the source the link
+    /// was taken from.
+    #[serde(rename = "linkSources")]
+    pub link_sources: BTreeMap<Platform, LinkSource>,
+}
+
+/// Merge results for the same entity into one, unioning `links_by_platform`
+/// and `entities_by_unique_id`.
+///
+/// Returns `None` if `results` is empty.
+///
+/// # Arguments
+///
+/// * `results`: The results to merge, in order of precedence for
+///   [`MergePolicy::KeepFirst`].
+/// * `policy`: How to pick between links/entities present in several results.
+pub fn merge(results: &[LinksAPIResult], policy: &MergePolicy) -> Option<MergedLinksResult> {
+    let first = results.first()?;
+
+    let mut merged = LinksAPIResult {
+        entity_unique_id: first.entity_unique_id.clone(),
+        user_country: first.user_country.clone(),
+        page_url: first.page_url.clone(),
+        links_by_platform: BTreeMap::new(),
+        entities_by_unique_id: BTreeMap::new(),
+    };
+    let mut link_sources = BTreeMap::<Platform, LinkSource>::new();
+    let mut entity_sources = HashMap::<String, usize>::new();
+
+    for (index, result) in results.iter().enumerate() {
+        for (platform, link) in result.links_by_platform.iter() {
+            let replace = match link_sources.get(platform) {
+                Some(source) => policy.should_replace(&results[source.index], result),
+                None => true,
+            };
+            if replace {
+                merged.links_by_platform.insert(platform.clone(), link.clone());
+                link_sources.insert(
+                    platform.clone(),
+                    LinkSource {
+                        index,
+                        entity_unique_id: result.entity_unique_id.clone(),
+                        user_country: result.user_country.clone(),
+                    },
+                );
+            }
+        }
+
+        for (unique_id, entity) in result.entities_by_unique_id.iter() {
+            let replace = match entity_sources.get(unique_id) {
+                Some(source) => policy.should_replace(&results[*source], result),
+                None => true,
+            };
+            if replace {
+                merged.entities_by_unique_id.insert(unique_id.clone(), entity.clone());
+                entity_sources.insert(unique_id.clone(), index);
+            }
+        }
+    }
+
+    Some(MergedLinksResult { result: merged, link_sources })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ResultBuilder;
+
+    /// A result looked up in `country`, linking Spotify to `spotify_url`
+    /// and Deezer if `deezer_url` is given.
+    fn result(
+        unique_id: &str,
+        country: &str,
+        spotify_url: &str,
+        deezer_url: Option<&str>,
+    ) -> LinksAPIResult {
+        let mut builder = ResultBuilder::new(unique_id)
+            .country(country)
+            .link(Platform::Spotify, "SPOTIFY_SONG::1", spotify_url)
+            .entity("SPOTIFY_SONG::1", &format!("Sugar ({country})"), "Maroon 5");
+        if let Some(url) = deezer_url {
+            builder = builder.link(Platform::Deezer, "DEEZER_SONG::2", url);
+        }
+        builder.build()
+    }
+
+    fn sources() -> Vec<LinksAPIResult> {
+        vec![
+            result("SPOTIFY_SONG::1", "US", "https://spotify/us", None),
+            result("DEEZER_SONG::2", "GB", "https://spotify/gb", Some("https://deezer/gb")),
+            result("SPOTIFY_SONG::1", "FR", "https://spotify/fr", Some("https://deezer/fr")),
+        ]
+    }
+
+    fn spotify_url(merged: &MergedLinksResult) -> &str {
+        &merged.result.links_by_platform[&Platform::Spotify].url
+    }
+
+    #[test]
+    fn nothing_to_merge() {
+        assert!(merge(&[], &MergePolicy::KeepFirst).is_none());
+    }
+
+    #[test]
+    fn keep_first() {
+        let merged = merge(&sources(), &MergePolicy::KeepFirst).unwrap();
+        assert_eq!(spotify_url(&merged), "https://spotify/us");
+        // Platforms missing from the first result come from the next one.
+        assert_eq!(merged.result.links_by_platform[&Platform::Deezer].url, "https://deezer/gb");
+        assert_eq!(merged.link_sources[&Platform::Deezer].index, 1);
+        assert_eq!(merged.link_sources[&Platform::Deezer].entity_unique_id, "DEEZER_SONG::2");
+        let title = merged.result.entities_by_unique_id["SPOTIFY_SONG::1"].title.as_deref();
+        assert_eq!(title, Some("Sugar (US)"));
+        // The top level fields come from the first result.
+        assert_eq!(merged.result.user_country, "US");
+        assert_eq!(merged.result.entity_unique_id, "SPOTIFY_SONG::1");
+    }
+
+    #[test]
+    fn keep_last() {
+        let merged = merge(&sources(), &MergePolicy::KeepLast).unwrap();
+        assert_eq!(spotify_url(&merged), "https://spotify/fr");
+        assert_eq!(merged.link_sources[&Platform::Spotify].user_country, "FR");
+        let title = merged.result.entities_by_unique_id["SPOTIFY_SONG::1"].title.as_deref();
+        assert_eq!(title, Some("Sugar (FR)"));
+        assert_eq!(merged.result.user_country, "US");
+    }
+
+    #[test]
+    fn prefer_country() {
+        let policy = MergePolicy::PreferCountry("GB".to_string());
+        let merged = merge(&sources(), &policy).unwrap();
+        assert_eq!(spotify_url(&merged), "https://spotify/gb");
+        assert_eq!(merged.result.links_by_platform[&Platform::Deezer].url, "https://deezer/gb");
+
+        // Without a result for the country, the first one wins.
+        let policy = MergePolicy::PreferCountry("DE".to_string());
+        let merged = merge(&sources(), &policy).unwrap();
+        assert_eq!(spotify_url(&merged), "https://spotify/us");
+        assert_eq!(merged.result.links_by_platform[&Platform::Deezer].url, "https://deezer/gb");
+    }
+}
@@ -5,8 +5,11 @@ use crate::OdesliError;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use strum::EnumIter;
 
-#[derive(Clone, Debug, EnumIter, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, EnumIter, Eq, Hash, Ord, PartialEq, PartialOrd)]
 /// `Platforms` as defined in the documentation.
+///
+/// Platforms are ordered as they are declared here, which is the order they
+/// are listed in results.
 pub enum Platform {
     Spotify,
     #[allow(non_camel_case_types)]
@@ -132,6 +135,56 @@ impl Platform {
             Self::Bandcamp => "bandcamp",
         }
     }
+
+    /// The name of the platform, as shown to people.
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Spotify => "Spotify",
+            Self::iTunes => "iTunes",
+            Self::AppleMusic => "Apple Music",
+            Self::YouTube => "YouTube",
+            Self::YouTubeMusic => "YouTube Music",
+            Self::Google => "Google Play Music",
+            Self::GoogleStore => "Google Play Store",
+            Self::Pandora => "Pandora",
+            Self::Deezer => "Deezer",
+            Self::Tidal => "TIDAL",
+            Self::AmazonStore => "Amazon",
+            Self::AmazonMusic => "Amazon Music",
+            Self::SoundCloud => "SoundCloud",
+            Self::Napster => "Napster",
+            Self::Yandex => "Yandex Music",
+            Self::Spinrilla => "Spinrilla",
+            Self::Audius => "Audius",
+            Self::Anghami => "Anghami",
+            Self::Boomplay => "Boomplay",
+            Self::Audiomack => "Audiomack",
+            Self::Bandcamp => "Bandcamp",
+        }
+    }
+
+    /// The [`APIProvider`] that powers the links for this platform.
+    pub fn api_provider(&self) -> APIProvider {
+        match self {
+            Self::Spotify => APIProvider::Spotify,
+            Self::iTunes | Self::AppleMusic => APIProvider::iTunes,
+            Self::YouTube | Self::YouTubeMusic => APIProvider::YouTube,
+            Self::Google | Self::GoogleStore => APIProvider::Google,
+            Self::Pandora => APIProvider::Pandora,
+            Self::Deezer => APIProvider::Deezer,
+            Self::Tidal => APIProvider::Tidal,
+            Self::AmazonStore | Self::AmazonMusic => APIProvider::Amazon,
+            Self::SoundCloud => APIProvider::SoundCloud,
+            Self::Napster => APIProvider::Napster,
+            Self::Yandex => APIProvider::Yandex,
+            Self::Spinrilla => APIProvider::Spinrilla,
+            Self::Audius => APIProvider::Audius,
+            Self::Anghami => APIProvider::Anghami,
+            Self::Boomplay => APIProvider::Boomplay,
+            Self::Audiomack => APIProvider::Audiomack,
+            Self::Bandcamp => APIProvider::Bandcamp,
+        }
+    }
 }
 
 impl Serialize for Platform {
@@ -227,6 +280,31 @@ impl APIProvider {
     }
 }
 
+impl APIProvider {
+    /// The [`Platform`] to look up IDs from this provider on.
+    pub fn platform(&self) -> Platform {
+        match self {
+            Self::Spotify => Platform::Spotify,
+            Self::iTunes => Platform::iTunes,
+            Self::YouTube => Platform::YouTube,
+            Self::Google => Platform::Google,
+            Self::Pandora => Platform::Pandora,
+            Self::Deezer => Platform::Deezer,
+            Self::Tidal => Platform::Tidal,
+            Self::Amazon => Platform::AmazonMusic,
+            Self::SoundCloud => Platform::SoundCloud,
+            Self::Napster => Platform::Napster,
+            Self::Yandex => Platform::Yandex,
+            Self::Spinrilla => Platform::Spinrilla,
+            Self::Audius => Platform::Audius,
+            Self::Anghami => Platform::Anghami,
+            Self::Boomplay => Platform::Boomplay,
+            Self::Audiomack => Platform::Audiomack,
+            Self::Bandcamp => Platform::Bandcamp,
+        }
+    }
+}
+
 impl Serialize for APIProvider {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -0,0 +1,221 @@
+use std::collections::HashSet;
+
+/// A match returned by [`SearchIndex::search`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchHit {
+    /// The unique ID of the matching entity.
+    pub unique_id: String,
+    /// The title of the matching entity.
+    pub title: Option<String>,
+    /// The artist name of the matching entity.
+    pub artist_name: Option<String>,
+    /// How well the entity matches the query, from `0.0` to `1.0`.
+    pub score: f64,
+}
+
+struct IndexedEntity {
+    unique_id: String,
+    title: Option<String>,
+    artist_name: Option<String>,
+    title_trigrams: HashSet<String>,
+    artist_trigrams: HashSet<String>,
+    combined_trigrams: HashSet<String>,
+}
+
+/// An in-memory fuzzy search index over the titles and artist names of
+/// entities.
+///
+/// Matching is done on character trigrams of the normalized (lowercased,
+/// punctuation stripped) strings, so it tolerates typos, missing words and
+/// different punctuation.
+#[derive(Default)]
+pub struct SearchIndex {
+    entities: Vec<IndexedEntity>,
+}
+
+impl SearchIndex {
+    /// Hits scoring below this are not returned by [`Self::search`].
+    pub const MIN_SCORE: f64 = 0.3;
+
+    /// Add an entity to the index.
+    pub fn insert(
+        &mut self,
+        unique_id: String,
+        title: Option<String>,
+        artist_name: Option<String>,
+    ) {
+        let title_trigrams = trigrams(title.as_deref().unwrap_or_default());
+        let artist_trigrams = trigrams(artist_name.as_deref().unwrap_or_default());
+        let combined_trigrams = trigrams(&format!(
+            "{} {}",
+            artist_name.as_deref().unwrap_or_default(),
+            title.as_deref().unwrap_or_default()
+        ));
+
+        self.entities.push(IndexedEntity {
+            unique_id,
+            title,
+            artist_name,
+            title_trigrams,
+            artist_trigrams,
+            combined_trigrams,
+        });
+    }
+
+    /// Number of entities in the index.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Whether the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Find the entities best matching `query`, best match first.
+    ///
+    /// A query of the form `"artist - title"` is matched against the artist
+    /// name and the title separately (in either order), anything else is
+    /// matched against both of them at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `query`: What to search for.
+    /// * `limit`: The maximum number of hits to return.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        let query_trigrams = trigrams(query);
+        let parts =
+            query.split_once(" - ").map(|(first, second)| (trigrams(first), trigrams(second)));
+
+        let mut hits = self
+            .entities
+            .iter()
+            .filter_map(|entity| {
+                let mut score = similarity(&query_trigrams, &entity.combined_trigrams)
+                    .max(similarity(&query_trigrams, &entity.title_trigrams));
+
+                if let Some((first, second)) = parts.as_ref() {
+                    let artist_then_title = (similarity(first, &entity.artist_trigrams)
+                        + similarity(second, &entity.title_trigrams))
+                        / 2.0;
+                    let title_then_artist = (similarity(first, &entity.title_trigrams)
+                        + similarity(second, &entity.artist_trigrams))
+                        / 2.0;
+                    score = score.max(artist_then_title).max(title_then_artist);
+                }
+
+                (score >= Self::MIN_SCORE).then(|| SearchHit {
+                    unique_id: entity.unique_id.clone(),
+                    title: entity.title.clone(),
+                    artist_name: entity.artist_name.clone(),
+                    score,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        hits.sort_by(|a, b| {
+            b.score.total_cmp(&a.score).then_with(|| a.unique_id.cmp(&b.unique_id))
+        });
+        hits.truncate(limit);
+        hits
+    }
+}
+
+/// Lowercase and replace anything that is not alphanumeric with spaces.
+fn normalize(value: &str) -> String {
+    value
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn trigrams(value: &str) -> HashSet<String> {
+    let normalized = normalize(value);
+    if normalized.is_empty() {
+        return HashSet::new();
+    }
+
+    let padded = format!("  {normalized} ").chars().collect::<Vec<_>>();
+    padded.windows(3).map(|window| window.iter().collect()).collect()
+}
+
+/// Sørensen–Dice coefficient of two trigram sets.
+fn similarity(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    2.0 * a.intersection(b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> SearchIndex {
+        let mut index = SearchIndex::default();
+        for (unique_id, title, artist) in [
+            ("SPOTIFY_SONG::1", "Sugar", "Maroon 5"),
+            ("SPOTIFY_SONG::2", "Sugar", "Robin Schulz"),
+            ("SPOTIFY_SONG::3", "Memories", "Maroon 5"),
+            ("SPOTIFY_SONG::4", "Bohemian Rhapsody", "Queen"),
+        ] {
+            index.insert(unique_id.to_string(), Some(title.to_string()), Some(artist.to_string()));
+        }
+        index.insert("SPOTIFY_SONG::5".to_string(), None, None);
+        index
+    }
+
+    fn ids(hits: &[SearchHit]) -> Vec<&str> {
+        hits.iter().map(|hit| hit.unique_id.as_str()).collect()
+    }
+
+    #[test]
+    fn trigrams_are_normalized_and_padded() {
+        let expected = ["  a", " ab", "ab ", "b c", " c "].map(String::from);
+        assert_eq!(trigrams("ab c"), HashSet::from(expected));
+        assert_eq!(trigrams("A-b, C!"), trigrams("a b c"));
+        assert!(trigrams(" ?! ").is_empty());
+    }
+
+    #[test]
+    fn similarity_is_the_dice_coefficient() {
+        assert_eq!(similarity(&trigrams("sugar"), &trigrams("sugar")), 1.0);
+        assert_eq!(similarity(&trigrams("sugar"), &trigrams("")), 0.0);
+        assert_eq!(similarity(&trigrams("abc"), &trigrams("xyz")), 0.0);
+        let partial = similarity(&trigrams("sugar"), &trigrams("suger"));
+        assert!(partial > 0.0 && partial < 1.0);
+    }
+
+    #[test]
+    fn search_tolerates_typos() {
+        let hits = index().search("bohemain rapsody", 5);
+        assert_eq!(ids(&hits), ["SPOTIFY_SONG::4"]);
+        assert!(hits[0].score >= SearchIndex::MIN_SCORE && hits[0].score < 1.0);
+    }
+
+    #[test]
+    fn search_splits_artist_and_title() {
+        let hits = index().search("maroon 5 - sugar", 5);
+        assert_eq!(hits[0].unique_id, "SPOTIFY_SONG::1");
+        // In either order.
+        let hits = index().search("sugar - maroon 5", 5);
+        assert_eq!(hits[0].unique_id, "SPOTIFY_SONG::1");
+    }
+
+    #[test]
+    fn search_orders_equal_scores_by_id_and_truncates() {
+        let hits = index().search("sugar", 5);
+        assert_eq!(ids(&hits)[..2], ["SPOTIFY_SONG::1", "SPOTIFY_SONG::2"]);
+        assert_eq!(hits[0].score, hits[1].score);
+        assert_eq!(ids(&index().search("sugar", 1)), ["SPOTIFY_SONG::1"]);
+        assert!(index().search("sugar", 0).is_empty());
+    }
+
+    #[test]
+    fn search_drops_poor_matches() {
+        assert!(index().search("zzzz", 5).is_empty());
+        assert!(index().search("", 5).is_empty());
+    }
+}
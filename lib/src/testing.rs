@@ -0,0 +1,64 @@
+//! Helpers to build small [`LinksAPIResult`]s by hand in unit tests.
+
+use std::{collections::BTreeMap, str::FromStr};
+
+use crate::{Entity, EntityType, EntityUniqueId, Link, LinksAPIResult, Platform};
+
+/// Builds a [`LinksAPIResult`] for the US, looked up for `entity_unique_id`.
+pub(crate) struct ResultBuilder {
+    result: LinksAPIResult,
+}
+
+impl ResultBuilder {
+    pub(crate) fn new(entity_unique_id: &str) -> Self {
+        let id = EntityUniqueId::from_str(entity_unique_id).expect("valid unique ID").id;
+        Self {
+            result: LinksAPIResult {
+                entity_unique_id: entity_unique_id.to_string(),
+                user_country: "US".to_string(),
+                page_url: format!("https://song.link/s/{id}"),
+                links_by_platform: BTreeMap::new(),
+                entities_by_unique_id: BTreeMap::new(),
+            },
+        }
+    }
+
+    /// Link `platform` to `entity_unique_id` at `url`.
+    pub(crate) fn link(mut self, platform: Platform, entity_unique_id: &str, url: &str) -> Self {
+        let link = Link {
+            entity_unique_id: entity_unique_id.to_string(),
+            url: url.to_string(),
+            native_app_uri_mobile: None,
+            native_app_uri_desktop: None,
+        };
+        self.result.links_by_platform.insert(platform, link);
+        self
+    }
+
+    /// Add the entity `unique_id`, its type and provider taken from the ID.
+    pub(crate) fn entity(mut self, unique_id: &str, title: &str, artist: &str) -> Self {
+        let parsed = EntityUniqueId::from_str(unique_id).expect("valid unique ID");
+        let entity = Entity {
+            id: parsed.id.clone(),
+            entity_type: parsed.entity_type().unwrap_or(EntityType::Song),
+            title: Some(title.to_string()),
+            artist_name: Some(artist.to_string()),
+            thumbnail_url: None,
+            thumbnail_width: None,
+            thumbnail_height: None,
+            platforms: vec![parsed.provider.platform()],
+            api_provider: parsed.provider,
+        };
+        self.result.entities_by_unique_id.insert(unique_id.to_string(), entity);
+        self
+    }
+
+    pub(crate) fn country(mut self, country: &str) -> Self {
+        self.result.user_country = country.to_string();
+        self
+    }
+
+    pub(crate) fn build(self) -> LinksAPIResult {
+        self.result
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::OdesliError;
+
+use sha2::{Digest, Sha256};
+
+/// Distinguishes the temporary files of the downloads running concurrently in
+/// this process.
+static PARTIAL_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Default limit on the size of a downloaded thumbnail: 10 MiB.
+pub const DEFAULT_THUMBNAIL_SIZE_LIMIT: u64 = 10 * 1024 * 1024;
+
+/// A thumbnail stored in a [`ThumbnailCache`].
+#[derive(Clone, Debug)]
+pub struct CachedThumbnail {
+    /// Path to the image on disk.
+    pub path: PathBuf,
+    /// Hex encoded SHA-256 digest of the image, which is also its file name.
+    pub digest: String,
+    /// The `Content-Type` the image was served with.
+    pub content_type: String,
+    /// Size of the image in bytes.
+    pub size: u64,
+}
+
+/// Downloads thumbnails into a content-addressed directory on disk.
+///
+/// Each image is stored at `<dir>/<first two digest chars>/<digest>.<ext>`,
+/// where `<digest>` is the SHA-256 of the image, so identical images served by
+/// different platforms are only stored once.
+#[derive(Clone)]
+pub struct ThumbnailCache {
+    dir: PathBuf,
+    size_limit: u64,
+    http_client: reqwest::Client,
+}
+
+impl ThumbnailCache {
+    /// Create a cache storing images under `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            size_limit: DEFAULT_THUMBNAIL_SIZE_LIMIT,
+            http_client: reqwest::Client::default(),
+        }
+    }
+
+    /// Modify the maximum size (in bytes) of an image that will be downloaded.
+    pub fn with_size_limit(mut self, size_limit: u64) -> Self {
+        self.size_limit = size_limit;
+        self
+    }
+
+    /// Modify the [`reqwest::Client`] being used for downloading the images.
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.http_client = client;
+        self
+    }
+
+    /// The directory the images are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Download the image at `url` and store it in the cache.
+    ///
+    /// The download is aborted if the response is not an `image/*` or if it
+    /// exceeds the size limit.
+    pub async fn fetch(&self, url: &str) -> Result<CachedThumbnail, OdesliError> {
+        let mut res = self.http_client.get(url).send().await.map_err(OdesliError::ReqwestError)?;
+
+        let status_code = res.status();
+        if status_code.as_u16() != 200 {
+            let body = res.text().await.unwrap_or_default();
+            return Err(OdesliError::Non200StatusCode { status_code, body });
+        }
+
+        let content_type = res
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.split(';').next().unwrap_or_default().trim().to_lowercase())
+            .unwrap_or_default();
+        let extension = extension_for(&content_type)
+            .ok_or_else(|| OdesliError::UnexpectedContentType(content_type.clone()))?;
+
+        if let Some(length) = res.content_length() {
+            if length > self.size_limit {
+                return Err(OdesliError::ThumbnailTooLarge { limit: self.size_limit });
+            }
+        }
+
+        let mut image = Vec::new();
+        while let Some(chunk) = res.chunk().await.map_err(OdesliError::ReqwestError)? {
+            if (image.len() + chunk.len()) as u64 > self.size_limit {
+                return Err(OdesliError::ThumbnailTooLarge { limit: self.size_limit });
+            }
+            image.extend_from_slice(&chunk);
+        }
+
+        let digest =
+            Sha256::digest(&image).iter().map(|byte| format!("{byte:02x}")).collect::<String>();
+        let path = self.dir.join(&digest[..2]).join(format!("{digest}.{extension}"));
+
+        if !tokio::fs::try_exists(&path).await.map_err(OdesliError::IoError)? {
+            let dir = path.parent().expect("path has a parent");
+            tokio::fs::create_dir_all(dir).await.map_err(OdesliError::IoError)?;
+            // Write to a temporary file first so that an interrupted write never
+            // leaves a truncated image behind under its final name. The name is
+            // unique so that concurrent downloads of the same image don't write
+            // to the same file.
+            let partial = path.with_extension(format!(
+                "{extension}.{}-{}.part",
+                std::process::id(),
+                PARTIAL_COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            tokio::fs::write(&partial, &image).await.map_err(OdesliError::IoError)?;
+            if let Err(err) = tokio::fs::rename(&partial, &path).await {
+                let _ = tokio::fs::remove_file(&partial).await;
+                // Another download stored the same image in the meantime.
+                if !tokio::fs::try_exists(&path).await.unwrap_or(false) {
+                    return Err(OdesliError::IoError(err));
+                }
+            }
+        }
+
+        Ok(CachedThumbnail { path, digest, content_type, size: image.len() as u64 })
+    }
+}
+
+fn extension_for(content_type: &str) -> Option<&'static str> {
+    match content_type {
+        "image/jpeg" | "image/jpg" => Some("jpg"),
+        "image/png" => Some("png"),
+        "image/webp" => Some("webp"),
+        "image/gif" => Some("gif"),
+        "image/avif" => Some("avif"),
+        _ => None,
+    }
+}
@@ -0,0 +1,77 @@
+//! The API key must never appear in formatted errors.
+
+use odesli_rs::{ApiKey, ClientBuilder, EntityType, OdesliError, Platform, REDACTED};
+
+const KEY: &str = "s3cr3t+k/y=&";
+
+/// The forms the key could take in formatted output: as is, and URL encoded.
+fn key_forms() -> Vec<String> {
+    let encoded = reqwest::Url::parse_with_params("http://localhost", [("key", KEY)]).unwrap();
+    let encoded = encoded.query().unwrap().trim_start_matches("key=").to_string();
+    vec![KEY.to_string(), encoded]
+}
+
+fn assert_redacted(formatted: &str) {
+    for form in key_forms() {
+        assert!(!formatted.contains(&form), "the API key leaked in {formatted:?}");
+    }
+}
+
+/// A base URL nothing listens on, so that requests fail to connect.
+fn unreachable_base_url() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+    format!("http://127.0.0.1:{port}")
+}
+
+async fn failed_request() -> OdesliError {
+    let client = ClientBuilder::default()
+        .with_api_key(KEY)
+        .with_base_url(unreachable_base_url())
+        .build();
+    client
+        .get_by_id("7CNUefGBVLn4cLoYv3ej8x", &Platform::Spotify, &EntityType::Song)
+        .await
+        .expect_err("nothing listens on the base URL")
+}
+
+#[tokio::test]
+async fn request_errors_redact_the_key() {
+    let error = failed_request().await;
+    assert!(matches!(error, OdesliError::ReqwestError(_)));
+
+    let display = error.to_string();
+    assert_redacted(&display);
+    assert_redacted(&format!("{error:?}"));
+    assert_redacted(&format!("{error:#?}"));
+    // The rest of the URL is kept, it helps debugging.
+    assert!(display.contains("7CNUefGBVLn4cLoYv3ej8x"), "{display}");
+    let redacted = reqwest::Url::parse_with_params("http://localhost", [("key", REDACTED)]).unwrap();
+    assert!(display.contains(redacted.query().unwrap()), "{display}");
+}
+
+#[tokio::test]
+async fn request_errors_redact_the_key_in_their_source() {
+    let error = failed_request().await;
+    let mut source = std::error::Error::source(&error);
+    let OdesliError::ReqwestError(inner) = &error else {
+        panic!("unexpected error {error}");
+    };
+    assert_redacted(&inner.to_string());
+    // The chain starts with the reqwest error itself, which carries the URL.
+    assert!(source.is_some_and(|source| source.is::<reqwest::Error>()), "{error:?}");
+    while let Some(error) = source {
+        assert_redacted(&error.to_string());
+        assert_redacted(&format!("{error:?}"));
+        source = error.source();
+    }
+}
+
+#[test]
+fn api_key_debug_is_redacted() {
+    let key = ApiKey::from(KEY);
+    assert_redacted(&format!("{key:?}"));
+    assert_redacted(&format!("{:?}", Some(key.clone())));
+    assert_eq!(key.expose(), KEY);
+}